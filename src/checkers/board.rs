@@ -8,7 +8,7 @@ use crate::checkers::player::Player::{Black, White};
 use crate::checkers::win_status::WinStatus;
 use crate::checkers::win_status::WinStatus::{Continue, Draw, Win};
 use std::collections::HashMap;
-use std::fmt::{Display, Formatter};
+use std::fmt::{Display, Formatter, Write};
 use std::process::exit;
 
 pub type Move = Vec<(i8, i8)>;
@@ -48,6 +48,26 @@ impl Board {
         board
     }
 
+    pub fn from_hash(hash: BoardHash) -> Board {
+        let (is_white, white_bitboard, black_bitboard, king_bitboard) = hash;
+        let mut board = Board {
+            white_bitboard,
+            black_bitboard,
+            king_bitboard,
+            current_player: if is_white { White } else { Black },
+            board_count: HashMap::new(),
+            moves_without_capture: 0,
+        };
+        board.incr_board_count();
+        board
+    }
+
+    #[cfg(test)]
+    pub fn with_moves_without_capture(mut self, moves_without_capture: i8) -> Board {
+        self.moves_without_capture = moves_without_capture;
+        self
+    }
+
     pub fn get(&self, x: i8, y: i8) -> Option<Piece> {
         let is_white = self.get_white_bitboard().is_some(x, y);
         let is_black = self.get_black_bitboard().is_some(x, y);
@@ -83,6 +103,10 @@ impl Board {
         self.moves_without_capture
     }
 
+    pub fn get_nb_pieces(&self) -> u32 {
+        self.get_any_bitboard().count_ones()
+    }
+
     pub fn get_win_status(&self) -> WinStatus {
         if self.is_draw() {
             Draw
@@ -176,6 +200,27 @@ impl Board {
         )
     }
 
+    /// Hash of the board state: unlike `hash`, it also depends on the draw counters, i.e. the number of times the
+    /// board was reached and the number of moves without capture, which the heuristics and the tablebase see.
+    /// The previous boards are not hashed, so that the move orders reaching the same board share a key:
+    /// the scores depending on them (repetitions of earlier boards) must not be stored under it
+    pub fn state_hash(&self) -> u64 {
+        let counters =
+            (self.get_board_count() as u64) << 8 | self.get_moves_without_capture() as u64;
+        mix(key_of_hash(self.hash()) ^ counters)
+    }
+
+    /// Hash of the whole board state, for exact searches (cf `ProofNumberSearch`): unlike `state_hash`, it depends on
+    /// the exact number of moves without capture, and on the boards reached since the last capture or man move,
+    /// which may be repeated later. The move orders reaching the same boards share a key
+    pub fn exact_state_hash(&self) -> u64 {
+        let previous_boards = self.board_count.iter().fold(0, |key, (&hash, &count)| {
+            key ^ mix(key_of_hash(hash) ^ count as u64)
+        });
+        mix(key_of_hash(self.hash())
+            ^ mix(previous_boards ^ self.get_moves_without_capture() as u64))
+    }
+
     fn get_white_bitboard(&self) -> u32 {
         self.white_bitboard
     }
@@ -336,7 +381,7 @@ impl Board {
         false
     }
 
-    fn can_jump(&self) -> bool {
+    pub fn can_jump(&self) -> bool {
         let man_directions = if self.get_player_is_white() {
             DIRECTIONS_MAN_WHITE
         } else {
@@ -460,6 +505,17 @@ pub fn char_of_y(y: i8) -> char {
     (y as u8 + b'1') as char
 }
 
+pub fn string_of_move(m: &Move) -> String {
+    let mut str = String::new();
+    for &(x, y) in m {
+        write!(str, "{}{} -> ", char_of_x(x), char_of_y(y)).unwrap();
+    }
+    for _ in 0.." -> ".len() {
+        str.pop();
+    }
+    str
+}
+
 fn add_moves_moving(board: &Board, moves: &mut Vec<Move>, directions: &[(i8, i8)], x: i8, y: i8) {
     for &(dx, dy) in directions {
         let (x2, y2) = (x + dx, y + dy);
@@ -510,6 +566,19 @@ fn add_moves_jumping(
     moves_indexes
 }
 
+fn key_of_hash(hash: BoardHash) -> u64 {
+    let (is_white, white_bitboard, black_bitboard, king_bitboard) = hash;
+    let pieces = mix((white_bitboard as u64) << 32 | black_bitboard as u64);
+    mix(pieces ^ ((king_bitboard as u64) << 1 | is_white as u64))
+}
+
+fn mix(x: u64) -> u64 {
+    // SplitMix64 finalizer
+    let x = (x ^ (x >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    let x = (x ^ (x >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    x ^ (x >> 31)
+}
+
 fn get_directions(piece: Piece) -> &'static [(i8, i8)] {
     if piece.is_king() {
         DIRECTIONS_KING
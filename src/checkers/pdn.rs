@@ -0,0 +1,305 @@
+use crate::checkers::bitboard::BitBoard;
+use crate::checkers::board::{BOARD_SIZE, Board, Move, is_playable};
+use std::fmt::Write;
+
+// Portable Draughts Notation (cf <https://en.wikipedia.org/wiki/Portable_Draughts_Notation>), for English draughts.
+// In PDN, the first player is Black, on the squares 1 to 12: this program's White is PDN's Black, and conversely.
+// Squares are numbered from 1 (G1) to 32 (B8), each row from right to left.
+
+/// Squares of a row
+const SQUARES_PER_ROW: i8 = BOARD_SIZE / 2;
+/// Maximum length of a movetext line
+const LINE_LENGTH: usize = 80;
+/// Results ending a movetext
+const PDN_RESULTS: &[&str] = &["2-0", "1-1", "0-2", "1-0", "0-1", "1/2-1/2", "*"];
+
+/// Moves explored from a board, the first one being the main line and the others its variations
+pub struct MoveTree {
+    pub children: Vec<(Move, MoveTree)>,
+}
+
+/// Main line of a PDN game (cf `pdn_games`)
+pub struct PdnGame {
+    pub board: Board,
+    pub moves: Vec<Move>,
+    /// PDN result, from the POV of PDN's White (cf `pdn_game`)
+    pub result: String,
+}
+
+pub fn square_of_position(x: i8, y: i8) -> u8 {
+    assert!(is_playable(x, y));
+    (y * SQUARES_PER_ROW + (BOARD_SIZE - 1 - x) / 2 + 1) as u8
+}
+
+pub fn position_of_square(square: u8) -> Option<(i8, i8)> {
+    if !(1..=(BOARD_SIZE * SQUARES_PER_ROW) as u8).contains(&square) {
+        return None;
+    }
+    let index = square as i8 - 1;
+    let y = index / SQUARES_PER_ROW;
+    let x = BOARD_SIZE - 2 - 2 * (index % SQUARES_PER_ROW) + y % 2;
+    Some((x, y))
+}
+
+/// E.g. `9-13` for a move, `9x18x27` for a multiple jump
+pub fn pdn_of_move(m: &Move) -> String {
+    let is_jump = i8::abs(m[1].1 - m[0].1) == 2;
+    m.iter()
+        .map(|&(x, y)| square_of_position(x, y).to_string())
+        .collect::<Vec<_>>()
+        .join(if is_jump { "x" } else { "-" })
+}
+
+/// Inverse of `pdn_of_move`, also accepting multiple jumps given by their first and last squares only (e.g. `9x27`).
+/// `None` if `pdn` is not a legal move of `board`, or if it is ambiguous
+pub fn move_of_pdn(board: &Board, pdn: &str) -> Option<Move> {
+    let squares = pdn
+        .split(['-', 'x'])
+        .map(|square| square.parse::<u8>().ok())
+        .collect::<Option<Vec<_>>>()?;
+    let mut candidates = board.possible_moves().into_iter().filter(|m| {
+        let move_squares = m
+            .iter()
+            .map(|&(x, y)| square_of_position(x, y))
+            .collect::<Vec<_>>();
+        move_squares == squares
+            || (squares.len() == 2
+                && move_squares.first() == squares.first()
+                && move_squares.last() == squares.last())
+    });
+    let m = candidates.next()?;
+    match candidates.next() {
+        None => Some(m),
+        Some(_) => None,
+    }
+}
+
+/// E.g. `B:W21,22,K30:B1,2,K9` (player to move, then the squares of each color, kings prefixed by `K`).
+/// The draw counters are not part of the FEN
+pub fn fen_of_board(board: &Board) -> String {
+    let mut white_squares = Vec::new();
+    let mut black_squares = Vec::new();
+    for square in 1..=(BOARD_SIZE * SQUARES_PER_ROW) as u8 {
+        let (x, y) = position_of_square(square).unwrap();
+        if let Some(piece) = board.get(x, y) {
+            let square = format!("{}{}", if piece.is_king() { "K" } else { "" }, square);
+            // Colors are swapped (cf above)
+            if piece.is_white() {
+                black_squares.push(square);
+            } else {
+                white_squares.push(square);
+            }
+        }
+    }
+    format!(
+        "{}:W{}:B{}",
+        if board.get_player_is_white() {
+            "B"
+        } else {
+            "W"
+        },
+        white_squares.join(","),
+        black_squares.join(",")
+    )
+}
+
+/// Inverse of `fen_of_board`, also accepting square ranges (e.g. `B:W21-32:B1-12`). `None` if `fen` is invalid
+pub fn board_of_fen(fen: &str) -> Option<Board> {
+    let mut parts = fen.trim().trim_end_matches('.').split(':');
+    let player_is_white = match parts.next()? {
+        "B" => true,
+        "W" => false,
+        _ => return None,
+    };
+    let mut white_bitboard = 0u32;
+    let mut black_bitboard = 0u32;
+    let mut king_bitboard = 0u32;
+    for part in parts {
+        let (color, squares) = part.split_at_checked(1)?;
+        // Colors are swapped (cf above)
+        let bitboard = match color {
+            "B" => &mut white_bitboard,
+            "W" => &mut black_bitboard,
+            _ => return None,
+        };
+        for square in squares.split(',').filter(|square| !square.is_empty()) {
+            let (is_king, square) = match square.strip_prefix('K') {
+                Some(square) => (true, square),
+                None => (false, square),
+            };
+            let (first, last) = square.split_once('-').unwrap_or((square, square));
+            for square in first.parse().ok()?..=last.parse().ok()? {
+                let (x, y) = position_of_square(square)?;
+                bitboard.set(x, y, true);
+                king_bitboard.set(x, y, is_king);
+            }
+        }
+    }
+    if white_bitboard & black_bitboard != 0 {
+        return None;
+    }
+    Some(Board::from_hash((
+        player_is_white,
+        white_bitboard,
+        black_bitboard,
+        king_bitboard,
+    )))
+}
+
+/// PDN game starting from `board` (cf `fen_of_board`), with `tree` as main line and variations.
+/// `result` is the PDN result, from the POV of PDN's White (e.g. `2-0`, `1-1`, `0-2`, or `*` if unknown)
+pub fn pdn_game(
+    tags: &[(&str, &str)],
+    board: &Board,
+    comment: &str,
+    tree: &MoveTree,
+    result: &str,
+) -> String {
+    let mut pdn = String::new();
+    for (name, value) in tags {
+        writeln!(pdn, "[{} \"{}\"]", name, value).unwrap();
+    }
+    writeln!(pdn, "[GameType \"21\"]").unwrap();
+    writeln!(pdn, "[FEN \"{}\"]", fen_of_board(board)).unwrap();
+    writeln!(pdn, "[Result \"{}\"]", result).unwrap();
+    writeln!(pdn).unwrap();
+
+    let mut movetext = format!("{{{}}}", comment);
+    // The first player moves on even plies
+    let first_ply = usize::from(!board.get_player_is_white());
+    write_variations(&mut movetext, tree, first_ply, true);
+    movetext.push(' ');
+    movetext.push_str(result);
+    let mut line = String::new();
+    for word in movetext.split(' ') {
+        if !line.is_empty() && line.len() + 1 + word.len() > LINE_LENGTH {
+            writeln!(pdn, "{}", line).unwrap();
+            line.clear();
+        }
+        if !line.is_empty() {
+            line.push(' ');
+        }
+        line.push_str(word);
+    }
+    writeln!(pdn, "{}", line).unwrap();
+    pdn
+}
+
+fn write_variations(movetext: &mut String, tree: &MoveTree, ply: usize, force_number: bool) {
+    let Some(((m, subtree), variations)) = tree.children.split_first() else {
+        return;
+    };
+    write_move(movetext, m, ply, force_number);
+    for (variation, variation_tree) in variations {
+        movetext.push_str(" (");
+        write_move(movetext, variation, ply, true);
+        write_variations(movetext, variation_tree, ply + 1, false);
+        movetext.push(')');
+    }
+    // After variations, the move number is repeated
+    write_variations(movetext, subtree, ply + 1, !variations.is_empty());
+}
+
+fn write_move(movetext: &mut String, m: &Move, ply: usize, force_number: bool) {
+    if !movetext.ends_with('(') {
+        movetext.push(' ');
+    }
+    if ply.is_multiple_of(2) {
+        write!(movetext, "{}. ", ply / 2 + 1).unwrap();
+    } else if force_number {
+        write!(movetext, "{}... ", ply / 2 + 1).unwrap();
+    }
+    movetext.push_str(&pdn_of_move(m));
+}
+
+/// Main lines of the games of a PDN file, e.g. written by `pdn_game`. Comments and variations are skipped.
+/// A game stops at its first illegal move
+pub fn pdn_games(pdn: &str) -> Vec<PdnGame> {
+    let mut games = Vec::new();
+    let mut tags = Vec::new();
+    let mut tokens = Vec::new();
+    let mut chars = pdn.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '[' => {
+                if !tokens.is_empty() {
+                    games.extend(pdn_game_of_tokens(&tags, &tokens));
+                    tags.clear();
+                    tokens.clear();
+                }
+                tags.push(chars.by_ref().take_while(|&c| c != ']').collect::<String>());
+            }
+            '{' => chars.by_ref().take_while(|&c| c != '}').for_each(drop),
+            '(' => {
+                let mut nb_open_variations = 1;
+                for c in chars.by_ref() {
+                    match c {
+                        '(' => nb_open_variations += 1,
+                        ')' => nb_open_variations -= 1,
+                        _ => (),
+                    }
+                    if nb_open_variations == 0 {
+                        break;
+                    }
+                }
+            }
+            c if c.is_whitespace() => (),
+            c => {
+                let mut token = c.to_string();
+                while let Some(&c) = chars.peek()
+                    && !c.is_whitespace()
+                    && !"[{(".contains(c)
+                {
+                    token.push(c);
+                    chars.next();
+                }
+                tokens.push(token);
+            }
+        }
+    }
+    if !tags.is_empty() || !tokens.is_empty() {
+        games.extend(pdn_game_of_tokens(&tags, &tokens));
+    }
+    games
+}
+
+/// `None` if the `FEN` tag is invalid
+fn pdn_game_of_tokens(tags: &[String], tokens: &[String]) -> Option<PdnGame> {
+    let tag_value = |name: &str| {
+        tags.iter().find_map(|tag| {
+            let (tag_name, value) = tag.split_once(' ')?;
+            (tag_name == name).then(|| value.trim().trim_matches('"').to_string())
+        })
+    };
+    let board = match tag_value("FEN") {
+        Some(fen) => board_of_fen(&fen)?,
+        None => Board::new(),
+    };
+    let mut result = tag_value("Result").unwrap_or_else(|| "*".to_string());
+    let mut moves = Vec::new();
+    let mut current_board = board.clone();
+    for token in tokens {
+        if PDN_RESULTS.contains(&token.as_str()) {
+            result.clone_from(token);
+            break;
+        }
+        // Move number (e.g. `12.` or `12...`), annotations (e.g. `!?`) and NAGs (e.g. `$1`) are skipped
+        let token = token
+            .rsplit_once('.')
+            .map_or(token.as_str(), |(_, m)| m)
+            .trim_end_matches(['!', '?']);
+        if token.is_empty() || token.starts_with('$') {
+            continue;
+        }
+        let Some(m) = move_of_pdn(&current_board, token) else {
+            break;
+        };
+        current_board.play(&m);
+        moves.push(m);
+    }
+    Some(PdnGame {
+        board,
+        moves,
+        result,
+    })
+}
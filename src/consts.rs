@@ -1,7 +1,19 @@
-use crate::consts::Mode::{Play, Tournament, Train};
+use crate::checkers::board::Board;
+use crate::checkers::pdn::board_of_fen;
+use crate::consts::Mode::{
+    Bench, Book, LevelMatch, MctsMatch, Play, Quantization, SearchMatch, Solve, Tablebase,
+    Tournament, Train,
+};
 use crate::neural_network;
-use crate::players::neural_network::get_move::ChooseMoveStrategy;
-use crate::players::neural_network::get_move::ChooseMoveStrategy::{DepthLimit, TimeLimit};
+use crate::players::alpha_beta::strength::StrengthLevel;
+use crate::players::mcts::get_move::Playout;
+use crate::players::neural_network::get_move::ChooseMoveStrategy::{
+    DepthLimit, TimeLimit, Training,
+};
+use crate::players::neural_network::get_move::{ChooseMoveStrategy, NeuralNetworkEvaluation};
+use crate::players::utils::alpha_beta::SearchParameters;
+use crate::players::utils::opening_book::BookChoice;
+use crate::players::utils::time_control::TimeControl;
 use std::time::Duration;
 
 #[derive(Eq, PartialEq)]
@@ -9,16 +21,160 @@ pub enum Mode {
     Play,
     Train,
     Tournament,
+    Tablebase,
+    SearchMatch,
+    Bench,
+    MctsMatch,
+    Solve,
+    LevelMatch,
+    Book,
+    Quantization,
 }
 
 /// Default time spent by the AI to choose its next move
 pub const TIME_PER_MOVE: Duration = Duration::from_secs(1);
+/// Time controls of the AI in `play` mode, chosen by the argument following `play` (the first one by default)
+pub const TIME_CONTROLS: &[(&str, TimeControl)] = &[
+    (
+        "fischer",
+        TimeControl::Fischer {
+            initial: Duration::from_mins(3),
+            increment: Duration::from_secs(2),
+        },
+    ),
+    (
+        "sudden_death",
+        TimeControl::SuddenDeath(Duration::from_mins(5)),
+    ),
+    (
+        "moves_per_period",
+        TimeControl::MovesPerPeriod {
+            moves: 20,
+            period: Duration::from_mins(2),
+        },
+    ),
+];
+/// Strength levels of the AI in `play` mode, from the weakest to the strongest (the default one).
+/// Each level is stronger than the previous one (cf `level_match`)
+pub const STRENGTH_LEVELS: &[(&str, StrengthLevel)] = &[
+    (
+        "beginner",
+        StrengthLevel {
+            max_depth: Some(2),
+            max_time: Some(Duration::from_millis(50)),
+            eval_noise: 1.,
+            blunder_probability: 0.15,
+        },
+    ),
+    (
+        "casual",
+        StrengthLevel {
+            max_depth: Some(4),
+            max_time: Some(Duration::from_millis(100)),
+            eval_noise: 0.5,
+            blunder_probability: 0.05,
+        },
+    ),
+    (
+        "intermediate",
+        StrengthLevel {
+            max_depth: Some(6),
+            max_time: Some(Duration::from_millis(250)),
+            eval_noise: 0.2,
+            blunder_probability: 0.02,
+        },
+    ),
+    (
+        "advanced",
+        StrengthLevel {
+            max_depth: Some(8),
+            max_time: Some(Duration::from_millis(500)),
+            eval_noise: 0.1,
+            blunder_probability: 0.01,
+        },
+    ),
+    ("max", StrengthLevel::FULL),
+];
+/// Time spent analysing each move of the human in `play` mode (cf `multi_pv`)
+pub const ANALYSIS_TIME_PER_MOVE: Duration = Duration::from_millis(500);
 /// Choose move strategy using time limit
 pub const TIME_LIMIT_STRATEGY: ChooseMoveStrategy = TimeLimit(TIME_PER_MOVE);
 /// Default alpha beta exploration depth to choose the AI next move
 pub const DEPTH_LIMIT: i8 = 4;
 /// Choose move strategy using depth limit
 pub const DEPTH_LIMIT_STRATEGY: ChooseMoveStrategy = DepthLimit(DEPTH_LIMIT);
+/// Choose move strategy of the training self-play games.
+/// `Puct { limits, root_noise: true }` searches each move, instead of choosing it from the scores of the next boards only
+pub const SELF_PLAY_STRATEGY: ChooseMoveStrategy = Training;
+/// How the neural network heuristic scores the boards of a search. The accumulators always run on the CPU.
+/// The `quantization` report checks that `Quantized` stays close to the trained network (cf `MAX_MEAN_OUTPUT_ERROR`)
+pub const NN_EVALUATION: NeuralNetworkEvaluation = cfg_select! {
+    cublas => NeuralNetworkEvaluation::BatchedLeaves,
+    not(cublas) => NeuralNetworkEvaluation::Quantized,
+};
+/// Number of threads of a threaded search, sharing the transposition table. 0 means one thread per CPU core
+pub const NB_SEARCH_THREADS: usize = 0;
+/// Size (in MiB) of the transposition table of each AI player, kept during the whole game
+pub const TRANSPOSITION_TABLE_SIZE: usize = 64;
+/// Size (in MiB) of the transposition table of each AI player, for depth-limited games only
+pub const DEPTH_LIMIT_TRANSPOSITION_TABLE_SIZE: usize = 1;
+/// Search features used by the AI players
+pub const SEARCH_PARAMETERS: SearchParameters = SearchParameters {
+    quiescence_nodes: 1_000,
+    principal_variation_search: true,
+    aspiration_window: 100_000,
+    max_extensions: 4,
+    contempt: 50_000,
+};
+/// Search features of the analyses of the human moves in `play` mode: draws are scored as even
+pub const ANALYSIS_SEARCH_PARAMETERS: SearchParameters = SearchParameters {
+    contempt: 0,
+    ..SEARCH_PARAMETERS
+};
+/// Search features of the reference version, which `search_match` and `bench` compare with
+pub const BASELINE_SEARCH_PARAMETERS: SearchParameters = SearchParameters {
+    quiescence_nodes: 1_000,
+    principal_variation_search: false,
+    aspiration_window: 0,
+    max_extensions: 0,
+    contempt: 0,
+};
+/// Time spent by each AI to choose its next move during a `search_match`
+pub const MATCH_TIME_PER_MOVE: Duration = Duration::from_millis(100);
+/// Number of random moves played at the start of each `search_match` opening
+pub const MATCH_OPENING_LENGTH: usize = 4;
+/// Number of openings of a `search_match`, each one played twice (once per color)
+pub const MATCH_NB_OPENINGS: usize = 20;
+/// Time per move of a `level_match`, capped by the maximum time of each level (cf `StrengthLevel::max_time`)
+pub const LEVEL_MATCH_TIME_PER_MOVE: Duration = TIME_PER_MOVE;
+/// Playouts of the MCTS player in the `mcts_match` command
+pub const MCTS_PLAYOUT: Playout = Playout::Random;
+/// Search depth of each `bench` position
+pub const BENCH_DEPTH: i8 = 2 * 6;
+/// Size (in MiB) of the proof-number table of the `solve` command
+pub const SOLVE_TABLE_SIZE: usize = 256;
+/// If `true`, the `solve` command trusts the tablebase results instead of searching these positions
+pub const SOLVE_USE_TABLEBASE: bool = true;
+/// File where the `solve` command writes the trees proving its result
+pub const SOLVE_PDN_FILE: &str = "solve.pdn";
+/// File storing the endgame tablebase. If it does not exist, the AI plays without tablebase
+pub const TABLEBASE_FILE: &str = "tablebase.bin";
+/// File storing the opening book (cf `src/players/utils/opening_book.rs`). If it does not exist, the AI plays without book
+pub const BOOK_FILE: &str = "book.txt";
+/// How the neural network players choose among the book moves: randomly, so that self-play and tournament games differ
+pub const BOOK_CHOICE: BookChoice = BookChoice::Weighted;
+/// How the AI chooses among the book moves in `play` mode: the human faces the main lines
+pub const PLAY_BOOK_CHOICE: BookChoice = BookChoice::Best;
+/// Number of plies of each PDN game added to the book by the `book` command
+pub const BOOK_PLIES: usize = 16;
+/// Number of plies of the book built by searches, when the `book` command is given no PDN file
+pub const BOOK_SEARCH_PLIES: usize = 6;
+/// Maximum number of moves of each position of the book built by searches
+pub const BOOK_SEARCH_WIDTH: usize = 2;
+/// A move is added to the book built by searches if its score is at most this margin below the best move
+pub const BOOK_SEARCH_MARGIN: i64 = 100_000;
+/// Time spent searching each position of the book built by searches
+pub const BOOK_SEARCH_TIME: Duration = Duration::from_secs(1);
 
 /// The default number of parameters in each layer of the neural network, for newly created neural networks only
 // pub const DEFAULT_NODES_PER_LAYER: [usize; 4] = [130, 512, 256, 1];
@@ -51,11 +207,86 @@ pub type NeuralNetworkFloat = cfg_select! {
     not(f64_precision) => f32,
 };
 
+/// Options, each one followed by its value, anywhere in the arguments
+const OPTIONS: &[&str] = &["--seed", "--level"];
+
+/// Program arguments, without the options (cf `OPTIONS`)
+fn get_positional_args() -> Vec<String> {
+    let mut args = Vec::new();
+    let mut all_args = std::env::args();
+    while let Some(arg) = all_args.next() {
+        if OPTIONS.contains(&arg.as_str()) {
+            all_args.next();
+        } else {
+            args.push(arg);
+        }
+    }
+    args
+}
+
+/// Value of the option `name` (cf `OPTIONS`)
+fn get_option(name: &str) -> Option<String> {
+    let mut args = std::env::args().skip_while(|arg| arg != name);
+    args.next()?;
+    Some(args.next().unwrap_or_else(|| panic!("{} <value>", name)))
+}
+
+/// Seed of the random decisions, given by `--seed <seed>` (cf `src/random.rs`)
+pub fn get_seed() -> Option<u64> {
+    let seed = get_option("--seed")?;
+    Some(seed.parse().unwrap_or_else(|_| panic!("{}", seed)))
+}
+
+/// Strength level of the AI in `play` mode, given by `--level <name>` (cf `STRENGTH_LEVELS`)
+pub fn get_strength_level() -> (&'static str, StrengthLevel) {
+    match get_option("--level") {
+        None => *STRENGTH_LEVELS.last().unwrap(),
+        Some(name) => *STRENGTH_LEVELS
+            .iter()
+            .find(|(level_name, _)| *level_name == name)
+            .unwrap_or_else(|| panic!("{}", name)),
+    }
+}
+
 pub fn get_mode() -> Mode {
-    match std::env::args().nth(1).unwrap().as_str() {
+    match get_positional_args()[1].as_str() {
         "play" => Play,
         "train" => Train,
         "tournament" => Tournament,
+        "tablebase" => Tablebase,
+        "search_match" => SearchMatch,
+        "bench" => Bench,
+        "mcts_match" => MctsMatch,
+        "solve" => Solve,
+        "level_match" => LevelMatch,
+        "book" => Book,
+        "quantization" => Quantization,
         mode => panic!("{}", mode),
     }
 }
+
+pub fn get_time_control() -> TimeControl {
+    match get_positional_args().get(2) {
+        None => TIME_CONTROLS[0].1,
+        Some(name) => {
+            TIME_CONTROLS
+                .iter()
+                .find(|(time_control_name, _)| *time_control_name == name)
+                .unwrap_or_else(|| panic!("{}", name))
+                .1
+        }
+    }
+}
+
+/// Board solved by the `solve` command, given as a PDN FEN (cf `board_of_fen`), the initial board by default
+pub fn get_solve_board() -> Board {
+    match get_positional_args().get(2) {
+        None => Board::new(),
+        Some(fen) => board_of_fen(fen).unwrap_or_else(|| panic!("{}", fen)),
+    }
+}
+
+/// PDN files the `book` command builds the opening book from. Without files, the book is built by searches
+pub fn get_book_pdn_files() -> Vec<String> {
+    get_positional_args().into_iter().skip(2).collect()
+}
@@ -4,6 +4,7 @@ extern crate blas_src;
 mod checkers {
     pub mod bitboard;
     pub mod board;
+    pub mod pdn;
     pub mod piece;
     pub mod piece_type;
     pub mod player;
@@ -35,51 +36,184 @@ pub mod neural_network {
 }
 mod players {
     pub mod alpha_beta {
+        pub mod bench;
         pub mod get_move;
+        pub mod level_match;
+        pub mod multi_pv;
+        pub mod ponder;
         pub mod score;
+        pub mod search_match;
+        pub mod searcher;
+        pub mod strength;
     }
     pub mod human {
         pub mod get_move;
     }
+    pub mod mcts {
+        pub mod get_move;
+        pub mod mcts_match;
+        pub mod tree;
+    }
     pub mod neural_network {
+        pub mod accumulator;
         pub mod get_move;
+        pub mod puct;
+        pub mod quantized;
+    }
+    pub mod proof_number {
+        pub mod proof_table;
+        pub mod search;
+        pub mod solve;
     }
     pub mod utils {
         pub mod alpha_beta;
+        pub mod heuristic;
+        pub mod move_ordering;
+        pub mod opening_book;
+        pub mod tablebase;
+        pub mod time_control;
+        pub mod transposition_table;
     }
 }
 mod consts;
+mod random;
 
-use crate::checkers::board::Board;
+use crate::checkers::board::{Board, Move};
 use crate::checkers::player::Player::{Black, White};
 use crate::checkers::win_status::WinStatus::{Continue, Draw, Win};
-use crate::consts::Mode::{Play, Tournament, Train};
-use crate::consts::{TIME_LIMIT_STRATEGY, get_mode};
+use crate::consts::Mode::{
+    Bench, Book, LevelMatch, MctsMatch, Play, Quantization, SearchMatch, Solve, Tablebase,
+    Tournament, Train,
+};
+use crate::consts::{
+    ANALYSIS_SEARCH_PARAMETERS, ANALYSIS_TIME_PER_MOVE, BASELINE_SEARCH_PARAMETERS,
+    SEARCH_PARAMETERS, TABLEBASE_FILE, TRANSPOSITION_TABLE_SIZE, get_book_pdn_files, get_mode,
+    get_seed, get_solve_board, get_strength_level, get_time_control,
+};
 use crate::neural_network::storage::load_neural_network;
 use crate::neural_network::training::tournament::run_tournament;
 use crate::neural_network::training::train::train_loop;
+use crate::players::alpha_beta::bench::run_bench;
+use crate::players::alpha_beta::level_match::run_level_match;
+use crate::players::alpha_beta::multi_pv::multi_pv;
+use crate::players::alpha_beta::ponder::Ponder;
+use crate::players::alpha_beta::search_match::run_search_match;
 use crate::players::human::get_move::get_human_move;
-use crate::players::neural_network::get_move::get_neural_network_move;
+use crate::players::mcts::mcts_match::run_mcts_match;
+use crate::players::neural_network::get_move::{
+    ChooseMoveStrategy, get_neural_network_move_with_info, neural_network_heuristic,
+};
+use crate::players::neural_network::quantized::run_quantization_report;
+use crate::players::proof_number::solve::run_solve;
+use crate::players::utils::alpha_beta::SearchLimits;
+use crate::players::utils::heuristic::Heuristic;
+use crate::players::utils::opening_book::run_book;
+use crate::players::utils::tablebase::generate_tablebase;
+use crate::players::utils::time_control::{Clock, TimeBudget};
+use crate::players::utils::transposition_table::TranspositionTable;
+use crate::random::{Rng, seeded_rng};
+use rand::RngExt;
+use std::sync::Arc;
 use std::thread::sleep;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 fn main() {
+    let seed = get_seed().unwrap_or_else(rand::random);
+    println!("Seed: {} (replay with `--seed {}`)", seed, seed);
+    let mut rng = seeded_rng(seed);
     match get_mode() {
-        Play => play(),
-        Train => train_loop("neural_networks"),
-        Tournament => run_tournament("neural_networks"),
+        Play => play(&mut rng),
+        Train => train_loop("neural_networks", &mut rng),
+        Tournament => run_tournament("neural_networks", &mut rng),
+        Tablebase => generate_tablebase(TABLEBASE_FILE),
+        SearchMatch => run_search_match(SEARCH_PARAMETERS, BASELINE_SEARCH_PARAMETERS, &mut rng),
+        Bench => run_bench(SEARCH_PARAMETERS, BASELINE_SEARCH_PARAMETERS, &mut rng),
+        MctsMatch => run_mcts_match(&mut rng),
+        Solve => run_solve(&get_solve_board()),
+        LevelMatch => run_level_match(&mut rng),
+        Book => run_book(&get_book_pdn_files()),
+        Quantization => {
+            run_quantization_report(&load_neural_network("neural_network.txt"), &mut rng);
+        }
     }
 }
 
-fn play() {
+fn play(rng: &mut Rng) {
     let neural_network = load_neural_network("neural_network.txt");
+    let heuristic = Arc::new(neural_network_heuristic(&neural_network));
+    let transposition_table = Arc::new(TranspositionTable::new(TRANSPOSITION_TABLE_SIZE));
+    let mut tree = None;
+    let mut clock = Clock::new(get_time_control());
+    let (level_name, level) = get_strength_level();
+    println!("AI level: {}", level_name);
+    let noise_salt = rng.random();
     let mut board = Board::new();
+    // Human move expected by the AI (second move of its principal variation), and the search pondering it on a hit
+    let mut expected_move = None;
+    let mut ponder_hit = None;
     while !board.is_end_game() {
         println!("{}", board);
         let m = if board.get_player_is_white() {
-            get_human_move(&board)
+            let ponder = expected_move.take().map(|expected_move| {
+                let mut expected_board = board.clone();
+                expected_board.play(&expected_move);
+                Ponder::start(
+                    expected_board,
+                    &heuristic,
+                    SEARCH_PARAMETERS,
+                    &transposition_table,
+                    rng.random(),
+                )
+            });
+            let m = get_human_move(&board);
+            if let Some(ponder) = ponder {
+                let mut next_board = board.clone();
+                next_board.play(&m);
+                if ponder.is_hit(&next_board) {
+                    println!("> AI ponder hit");
+                    ponder_hit = Some(ponder);
+                } else {
+                    ponder.stop();
+                }
+            }
+            print_move_analysis(&board, &m, &*heuristic, &transposition_table);
+            m
         } else {
-            get_neural_network_move(&board, &neural_network, TIME_LIMIT_STRATEGY, true)
+            let t0 = Instant::now();
+            // On a ponder hit, the pondering search goes on, on the AI clock: no new search is started
+            let search_result = match ponder_hit.take() {
+                Some(ponder) => {
+                    let search_result = ponder.hit(clock.time_budget());
+                    if let Some(principal_variation) = search_result.principal_variations.last() {
+                        println!("> AI thinking (pondered): {}", principal_variation);
+                    }
+                    search_result
+                }
+                None => get_neural_network_move_with_info(
+                    &board,
+                    &neural_network,
+                    ChooseMoveStrategy::Clock {
+                        budget: clock.time_budget(),
+                        level,
+                        noise_salt,
+                    },
+                    true,
+                    &transposition_table,
+                    &mut Some(heuristic.clone()),
+                    &mut tree,
+                    rng,
+                    |search_info| println!("> AI thinking: {}", search_info),
+                ),
+            };
+            clock.play(t0.elapsed());
+            println!("> AI clock: {:?} left", clock.get_remaining());
+            // The AI only ponders at full strength: its pondered moves would not be weakened
+            expected_move = search_result
+                .principal_variations
+                .last()
+                .filter(|_| level.is_full_strength())
+                .and_then(|principal_variation| principal_variation.moves.get(1).cloned());
+            search_result.best_move
         };
         board.play(&m);
     }
@@ -92,3 +226,42 @@ fn play() {
     }
     sleep(Duration::from_secs(2));
 }
+
+/// Compares the move played by the human with the best moves (cf `multi_pv`)
+fn print_move_analysis(
+    board: &Board,
+    m: &Move,
+    heuristic: &impl Heuristic,
+    transposition_table: &TranspositionTable,
+) {
+    let nb_moves = board.possible_moves().len();
+    if nb_moves <= 1 {
+        return;
+    }
+    let lines = multi_pv(
+        board,
+        heuristic,
+        ANALYSIS_SEARCH_PARAMETERS,
+        nb_moves,
+        SearchLimits {
+            time: Some(TimeBudget::fixed(ANALYSIS_TIME_PER_MOVE)),
+            ..SearchLimits::default()
+        },
+        transposition_table,
+    );
+    let Some(rank) = lines.iter().position(|line| &line.moves[0] == m) else {
+        return;
+    };
+    let best_line = &lines[0];
+    if lines[rank].score == best_line.score {
+        println!("> Analysis: you played a best move ({})", lines[rank]);
+    } else {
+        println!(
+            "> Analysis: your move is ranked {}/{}, {} below the best move ({})",
+            rank + 1,
+            nb_moves,
+            best_line.score - lines[rank].score,
+            best_line
+        );
+    }
+}
@@ -3,7 +3,8 @@ use crate::consts::{
     NeuralNetwork, NeuralNetworkFloat,
 };
 use crate::neural_network::types::matrix::Matrix;
-use rand::random_range;
+use crate::random::Rng;
+use rand::RngExt;
 
 pub struct NeuralNetworkParameters {
     pub learning_rate: NeuralNetworkFloat,
@@ -23,7 +24,7 @@ pub trait NeuralNetworkTrait: Clone {
     fn train_once(&mut self, input: &Matrix, expected: NeuralNetworkFloat);
 }
 
-pub fn generate_neural_networks() -> Vec<NeuralNetwork> {
+pub fn generate_neural_networks(rng: &mut Rng) -> Vec<NeuralNetwork> {
     let mut neural_networks = Vec::new();
     let mut nb_parameters = 0;
     for i in 0..DEFAULT_NB_LAYERS - 1 {
@@ -35,15 +36,20 @@ pub fn generate_neural_networks() -> Vec<NeuralNetwork> {
     println!("***  {} parameters  ***", nb_parameters);
     println!("**************************************");
     for _ in 0..NB_NEURAL_NETWORKS {
-        let new_neural_network = NeuralNetwork::import(&generate_parameters(
-            &DEFAULT_NODES_PER_LAYER,
-            DEFAULT_LEARNING_RATE,
-        ));
-        neural_networks.push(new_neural_network);
+        neural_networks.push(generate_neural_network(rng));
     }
     neural_networks
 }
 
+/// Untrained neural network, with the default layers
+pub fn generate_neural_network(rng: &mut Rng) -> NeuralNetwork {
+    NeuralNetwork::import(&generate_parameters(
+        &DEFAULT_NODES_PER_LAYER,
+        DEFAULT_LEARNING_RATE,
+        rng,
+    ))
+}
+
 pub fn get_nodes_per_layer(parameters: &NeuralNetworkParameters) -> Vec<usize> {
     let nb_layers = parameters.biases.len();
     let mut nodes_per_layer = vec![0; nb_layers];
@@ -61,9 +67,10 @@ pub fn get_nodes_per_layer(parameters: &NeuralNetworkParameters) -> Vec<usize> {
 fn generate_parameters(
     nodes_per_layer: &[usize],
     learning_rate: NeuralNetworkFloat,
+    rng: &mut Rng,
 ) -> NeuralNetworkParameters {
-    let weights = init_weights(nodes_per_layer);
-    let biases = init_biases(nodes_per_layer);
+    let weights = init_weights(nodes_per_layer, rng);
+    let biases = init_biases(nodes_per_layer, rng);
     NeuralNetworkParameters {
         learning_rate,
         weights,
@@ -71,30 +78,30 @@ fn generate_parameters(
     }
 }
 
-fn init_weights(nodes_per_layer: &[usize]) -> Vec<Matrix> {
+fn init_weights(nodes_per_layer: &[usize], rng: &mut Rng) -> Vec<Matrix> {
     let mut weights = vec![Matrix::new(0, 0); nodes_per_layer.len()];
     for l in 1..nodes_per_layer.len() {
         let input_len = nodes_per_layer[l - 1];
         let output_len = nodes_per_layer[l];
-        weights[l] = init_matrix(output_len, input_len);
+        weights[l] = init_matrix(output_len, input_len, rng);
     }
     weights
 }
 
-fn init_biases(nodes_per_layer: &[usize]) -> Vec<Matrix> {
+fn init_biases(nodes_per_layer: &[usize], rng: &mut Rng) -> Vec<Matrix> {
     let mut biases = vec![Matrix::new(0, 0); nodes_per_layer.len()];
     for l in 1..nodes_per_layer.len() {
         let layer_len = nodes_per_layer[l];
-        biases[l] = init_matrix(layer_len, 1);
+        biases[l] = init_matrix(layer_len, 1, rng);
     }
     biases
 }
 
-fn init_matrix(height: usize, width: usize) -> Matrix {
+fn init_matrix(height: usize, width: usize, rng: &mut Rng) -> Matrix {
     let mut res = Matrix::new(height, width);
     for i in 0..res.height() {
         for j in 0..res.width() {
-            res.set(i, j, random_range(-1. ..1.));
+            res.set(i, j, rng.random_range(-1. ..1.));
         }
     }
     res
@@ -3,6 +3,7 @@ use crate::neural_network::neural_network::{
     NeuralNetworkParameters, NeuralNetworkTrait, generate_neural_networks, get_nodes_per_layer,
 };
 use crate::neural_network::types::matrix::Matrix;
+use crate::random::Rng;
 use std::fs;
 use std::fs::{File, create_dir, read_dir};
 use std::io::Write;
@@ -17,9 +18,10 @@ pub fn store_new_neural_networks(neural_networks: &[NeuralNetwork], folder: &str
     store_neural_networks(neural_networks, &new_folder_path);
 }
 
-pub fn load_latest_neural_networks(folder: &str) -> Vec<NeuralNetwork> {
+/// Without neural network yet, new ones are generated with `rng`
+pub fn load_latest_neural_networks(folder: &str, rng: &mut Rng) -> Vec<NeuralNetwork> {
     let folder_id = get_latest_folder_id(folder);
-    load_neural_networks(&format!("{folder}/{folder_id}"))
+    load_neural_networks(&format!("{folder}/{folder_id}"), rng)
 }
 
 pub fn load_all_neural_networks(folder: &str) -> Vec<NeuralNetwork> {
@@ -50,11 +52,11 @@ fn store_neural_networks(neural_networks: &[NeuralNetwork], folder: &str) {
     }
 }
 
-fn load_neural_networks(folder: &str) -> Vec<NeuralNetwork> {
+fn load_neural_networks(folder: &str, rng: &mut Rng) -> Vec<NeuralNetwork> {
     let mut files_in_folder = read_dir(folder).unwrap().peekable();
     if files_in_folder.peek().is_none() {
         // Empty folder
-        let neural_networks = generate_neural_networks();
+        let neural_networks = generate_neural_networks(rng);
         store_neural_networks(&neural_networks, folder);
         return neural_networks;
     }
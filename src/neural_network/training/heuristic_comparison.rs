@@ -1,19 +1,22 @@
 use crate::checkers::board::Board;
 use crate::checkers::win_status::WinStatus::{Continue, Draw, Win};
 use crate::consts::{
-    DEPTH_LIMIT, DEPTH_LIMIT_STRATEGY, NeuralNetwork, TIME_LIMIT_STRATEGY, TIME_PER_MOVE,
+    BOOK_CHOICE, DEPTH_LIMIT, DEPTH_LIMIT_STRATEGY, DEPTH_LIMIT_TRANSPOSITION_TABLE_SIZE,
+    NeuralNetwork, SEARCH_PARAMETERS, TIME_LIMIT_STRATEGY, TIME_PER_MOVE, TRANSPOSITION_TABLE_SIZE,
 };
 use crate::players::alpha_beta::get_move::{
     get_alpha_beta_move_depth_limit, get_alpha_beta_move_simple_heuristic_time_limit,
 };
 use crate::players::alpha_beta::score::naive_score;
 use crate::players::neural_network::get_move::get_neural_network_move;
+use crate::players::utils::transposition_table::TranspositionTable;
+use crate::random::Rng;
 use std::sync::Arc;
 
-pub fn compare_heuristics(neural_networks: &[NeuralNetwork]) {
+pub fn compare_heuristics(neural_networks: &[NeuralNetwork], rng: &mut Rng) {
     let (mut wins, mut draws, mut losses) = (0, 0, 0);
     for nn in neural_networks.iter().rev().take(100) {
-        play(nn, true, true, &mut wins, &mut draws, &mut losses);
+        play(nn, true, true, &mut wins, &mut draws, &mut losses, rng);
     }
     println!(
         "Plays white, Time limited - wins: {}, draws: {}, losses: {}",
@@ -22,7 +25,7 @@ pub fn compare_heuristics(neural_networks: &[NeuralNetwork]) {
 
     let (mut wins, mut draws, mut losses) = (0, 0, 0);
     for nn in neural_networks.iter().rev().take(100) {
-        play(nn, false, true, &mut wins, &mut draws, &mut losses);
+        play(nn, false, true, &mut wins, &mut draws, &mut losses, rng);
     }
     println!(
         "Plays black, Time limited - wins: {}, draws: {}, losses: {}",
@@ -31,7 +34,7 @@ pub fn compare_heuristics(neural_networks: &[NeuralNetwork]) {
 
     let (mut wins, mut draws, mut losses) = (0, 0, 0);
     for nn in neural_networks.iter().rev().take(100) {
-        play(nn, true, false, &mut wins, &mut draws, &mut losses);
+        play(nn, true, false, &mut wins, &mut draws, &mut losses, rng);
     }
     println!(
         "Plays white, Depth limited - wins: {}, draws: {}, losses: {}",
@@ -40,7 +43,7 @@ pub fn compare_heuristics(neural_networks: &[NeuralNetwork]) {
 
     let (mut wins, mut draws, mut losses) = (0, 0, 0);
     for nn in neural_networks.iter().rev().take(100) {
-        play(nn, false, false, &mut wins, &mut draws, &mut losses);
+        play(nn, false, false, &mut wins, &mut draws, &mut losses, rng);
     }
     println!(
         "Plays black, Depth limited - wins: {}, draws: {}, losses: {}",
@@ -55,24 +58,67 @@ fn play(
     wins: &mut u64,
     draws: &mut u64,
     losses: &mut u64,
+    rng: &mut Rng,
 ) {
+    let transposition_table_size = if is_time_limited {
+        TRANSPOSITION_TABLE_SIZE
+    } else {
+        DEPTH_LIMIT_TRANSPOSITION_TABLE_SIZE
+    };
+    let nn_transposition_table = Arc::new(TranspositionTable::new(transposition_table_size));
+    let transposition_table = Arc::new(TranspositionTable::new(transposition_table_size));
+    let (mut nn_heuristic, mut nn_tree) = (None, None);
     let mut board = Board::new();
     let mut nn_plays = nn_plays_white;
     while !board.is_end_game() {
         let m = if nn_plays {
             if is_time_limited {
-                get_neural_network_move(&board, neural_network, TIME_LIMIT_STRATEGY, true)
+                get_neural_network_move(
+                    &board,
+                    neural_network,
+                    TIME_LIMIT_STRATEGY,
+                    true,
+                    &nn_transposition_table,
+                    &mut nn_heuristic,
+                    &mut nn_tree,
+                    rng,
+                )
             } else {
-                get_neural_network_move(&board, neural_network, DEPTH_LIMIT_STRATEGY, true)
+                get_neural_network_move(
+                    &board,
+                    neural_network,
+                    DEPTH_LIMIT_STRATEGY,
+                    true,
+                    &nn_transposition_table,
+                    &mut nn_heuristic,
+                    &mut nn_tree,
+                    rng,
+                )
             }
         } else {
             if is_time_limited {
-                get_alpha_beta_move_simple_heuristic_time_limit(&board, TIME_PER_MOVE, true)
+                get_alpha_beta_move_simple_heuristic_time_limit(
+                    &board,
+                    SEARCH_PARAMETERS,
+                    TIME_PER_MOVE,
+                    BOOK_CHOICE,
+                    true,
+                    &transposition_table,
+                    rng,
+                )
             } else {
-                get_alpha_beta_move_depth_limit(&board, Arc::new(naive_score), DEPTH_LIMIT, true)
+                get_alpha_beta_move_depth_limit(
+                    &board,
+                    &Arc::new(naive_score),
+                    SEARCH_PARAMETERS,
+                    DEPTH_LIMIT,
+                    true,
+                    &transposition_table,
+                    rng,
+                )
             }
         };
-        board.play(&m);
+        board.play(&m.best_move);
         nn_plays = !nn_plays;
     }
     match board.get_win_status() {
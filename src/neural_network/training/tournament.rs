@@ -4,24 +4,24 @@ use crate::neural_network::storage::load_all_neural_networks;
 use crate::neural_network::training::graphs::display_results;
 use crate::neural_network::training::heuristic_comparison::compare_heuristics;
 use crate::neural_network::training::train::{TournamentResult, play_game};
+use crate::random::{Rng, spawn_seeded};
 #[cfg(not(nn_is_sync))]
 use std::ops::Deref;
 use std::sync::{Arc, Mutex};
-use std::thread;
 use std::time::Instant;
 
-pub fn run_tournament(folder: &str) {
+pub fn run_tournament(folder: &str, rng: &mut Rng) {
     let t0 = Instant::now();
     let neural_networks = load_all_neural_networks(folder);
     println!("{} neural networks", neural_networks.len());
     println!("{:?}", t0.elapsed());
-    let tournament_result = compute_tournament_result(&neural_networks);
+    let tournament_result = compute_tournament_result(&neural_networks, rng);
     println!("{:?}", t0.elapsed());
     display_results(&tournament_result);
-    compare_heuristics(&neural_networks);
+    compare_heuristics(&neural_networks, rng);
 }
 
-fn compute_tournament_result(neural_networks: &[NeuralNetwork]) -> TournamentResult {
+fn compute_tournament_result(neural_networks: &[NeuralNetwork], rng: &mut Rng) -> TournamentResult {
     let nb_neural_networks = neural_networks.len();
     let result = vec![vec![Continue; nb_neural_networks]; nb_neural_networks];
     let mut handle = Vec::new();
@@ -34,9 +34,10 @@ fn compute_tournament_result(neural_networks: &[NeuralNetwork]) -> TournamentRes
         };
         let result = result.clone();
         let nn1 = nn1.to_owned();
-        handle.push(thread::spawn(move || {
+        handle.push(spawn_seeded(rng, move |rng| {
             for (index2, nn2) in neural_networks.iter().enumerate() {
-                result.lock().unwrap()[index1][index2] = play_game(&nn1, nn2, DEPTH_LIMIT_STRATEGY);
+                result.lock().unwrap()[index1][index2] =
+                    play_game(&nn1, nn2, DEPTH_LIMIT_STRATEGY, rng);
             }
         }));
     }
@@ -2,17 +2,20 @@ use crate::checkers::board::Board;
 use crate::checkers::win_status::WinStatus;
 use crate::checkers::win_status::WinStatus::{Continue, Draw, Win};
 use crate::consts::{
-    DEPTH_LIMIT_STRATEGY, LEARNING_RATE_EVOLUTIONS, NB_LEARNING_RATES, NB_LEARNINGS_PER_RESULT,
-    NB_NEURAL_NETWORKS, NeuralNetwork, NeuralNetworkFloat, TIME_LIMIT_STRATEGY, TIME_PER_MOVE,
+    BOOK_CHOICE, DEPTH_LIMIT_STRATEGY, DEPTH_LIMIT_TRANSPOSITION_TABLE_SIZE,
+    LEARNING_RATE_EVOLUTIONS, NB_LEARNING_RATES, NB_LEARNINGS_PER_RESULT, NB_NEURAL_NETWORKS,
+    NeuralNetwork, NeuralNetworkFloat, SEARCH_PARAMETERS, SELF_PLAY_STRATEGY, TIME_LIMIT_STRATEGY,
+    TIME_PER_MOVE, TRANSPOSITION_TABLE_SIZE,
 };
 use crate::neural_network::neural_network::NeuralNetworkTrait;
 use crate::neural_network::storage::{load_latest_neural_networks, store_new_neural_networks};
 use crate::neural_network::types::matrix::Matrix;
 use crate::players::alpha_beta::get_move::get_alpha_beta_move_simple_heuristic_time_limit;
-use crate::players::neural_network::get_move::ChooseMoveStrategy::Training;
 use crate::players::neural_network::get_move::{
     ChooseMoveStrategy, get_neural_network_move, input_of_board,
 };
+use crate::players::utils::transposition_table::TranspositionTable;
+use crate::random::{Rng, spawn_seeded};
 use chrono::Local;
 #[cfg(not(nn_is_sync))]
 use std::ops::Deref;
@@ -29,25 +32,45 @@ struct TrainingResult {
     nb_draws: u64,
 }
 
-pub fn train_loop(folder: &str) {
+pub fn train_loop(folder: &str, rng: &mut Rng) {
     loop {
         let t0 = Instant::now();
-        let training_result = train(folder, Duration::from_mins(5), 8);
+        let training_result = train(folder, Duration::from_mins(5), 8, rng);
         println!(
             "Training with: {} games, {} draws",
             training_result.nb_games, training_result.nb_draws
         );
         println!("Spent {:?}", t0.elapsed());
         let nn = training_result.neural_networks[0].clone();
+        let nn_transposition_table = Arc::new(TranspositionTable::new(TRANSPOSITION_TABLE_SIZE));
+        let transposition_table = Arc::new(TranspositionTable::new(TRANSPOSITION_TABLE_SIZE));
+        let (mut nn_heuristic, mut nn_tree) = (None, None);
         let mut board = Board::new();
         while !board.is_end_game() {
             println!("{}", board);
             let m = if board.get_player_is_white() {
-                get_neural_network_move(&board, &nn, TIME_LIMIT_STRATEGY, true)
+                get_neural_network_move(
+                    &board,
+                    &nn,
+                    TIME_LIMIT_STRATEGY,
+                    true,
+                    &nn_transposition_table,
+                    &mut nn_heuristic,
+                    &mut nn_tree,
+                    rng,
+                )
             } else {
-                get_alpha_beta_move_simple_heuristic_time_limit(&board, TIME_PER_MOVE, true)
+                get_alpha_beta_move_simple_heuristic_time_limit(
+                    &board,
+                    SEARCH_PARAMETERS,
+                    TIME_PER_MOVE,
+                    BOOK_CHOICE,
+                    true,
+                    &transposition_table,
+                    rng,
+                )
             };
-            board.play(&m);
+            board.play(&m.best_move);
         }
         println!("{}", board);
         println!("{:?}", board.get_win_status());
@@ -64,38 +87,66 @@ pub fn play_game(
     white: &NeuralNetwork,
     black: &NeuralNetwork,
     move_strategy: ChooseMoveStrategy,
+    rng: &mut Rng,
 ) -> WinStatus {
+    let white_transposition_table = Arc::new(TranspositionTable::new(
+        DEPTH_LIMIT_TRANSPOSITION_TABLE_SIZE,
+    ));
+    let black_transposition_table = Arc::new(TranspositionTable::new(
+        DEPTH_LIMIT_TRANSPOSITION_TABLE_SIZE,
+    ));
+    let (mut white_heuristic, mut black_heuristic) = (None, None);
+    let (mut white_tree, mut black_tree) = (None, None);
     let mut board = Board::new();
     let mut white_plays = board.get_player_is_white();
     while !board.is_end_game() {
         if white_plays {
-            board.play(&get_neural_network_move(
-                &board,
-                white,
-                move_strategy,
-                false,
-            ));
+            board.play(
+                &get_neural_network_move(
+                    &board,
+                    white,
+                    move_strategy,
+                    false,
+                    &white_transposition_table,
+                    &mut white_heuristic,
+                    &mut white_tree,
+                    rng,
+                )
+                .best_move,
+            );
         } else {
-            board.play(&get_neural_network_move(
-                &board,
-                black,
-                move_strategy,
-                false,
-            ));
+            board.play(
+                &get_neural_network_move(
+                    &board,
+                    black,
+                    move_strategy,
+                    false,
+                    &black_transposition_table,
+                    &mut black_heuristic,
+                    &mut black_tree,
+                    rng,
+                )
+                .best_move,
+            );
         }
         white_plays = !white_plays;
     }
     board.get_win_status()
 }
 
-fn train(folder: &str, duration_per_training: Duration, nb_trainings: usize) -> TrainingResult {
-    let mut neural_networks = load_latest_neural_networks(folder);
+fn train(
+    folder: &str,
+    duration_per_training: Duration,
+    nb_trainings: usize,
+    rng: &mut Rng,
+) -> TrainingResult {
+    let mut neural_networks = load_latest_neural_networks(folder, rng);
     let mut nb_games = 0;
     let mut nb_draws = 0;
     for _ in 0..nb_trainings {
         let all_training_results =
-            train_different_learning_rate(&neural_networks, duration_per_training);
-        let training_result = keep_best_learning_rate(all_training_results);
+            train_different_learning_rate(&neural_networks, duration_per_training, rng);
+        let training_result = keep_best_learning_rate(all_training_results, rng);
         neural_networks = training_result.neural_networks;
         nb_games += training_result.nb_games;
         nb_draws += training_result.nb_draws;
@@ -111,6 +162,7 @@ fn train(folder: &str, duration_per_training: Duration, nb_trainings: usize) ->
 fn train_different_learning_rate(
     neural_networks: &[NeuralNetwork],
     duration: Duration,
+    rng: &mut Rng,
 ) -> Vec<TrainingResult> {
     let mut handle = Vec::new();
     for &rate_evolution in LEARNING_RATE_EVOLUTIONS {
@@ -118,8 +170,8 @@ fn train_different_learning_rate(
         for nn in &mut neural_networks {
             nn.change_learning_rate(rate_evolution);
         }
-        handle.push(thread::spawn(move || {
-            train_neural_networks(neural_networks, duration)
+        handle.push(spawn_seeded(rng, move |rng| {
+            train_neural_networks(neural_networks, duration, rng)
         }));
     }
     handle
@@ -128,7 +180,10 @@ fn train_different_learning_rate(
         .collect::<Vec<_>>()
 }
 
-fn keep_best_learning_rate(all_training_results: Vec<TrainingResult>) -> TrainingResult {
+fn keep_best_learning_rate(
+    all_training_results: Vec<TrainingResult>,
+    rng: &mut Rng,
+) -> TrainingResult {
     let mut handle = Vec::new();
     let all_training_results = Arc::new(all_training_results);
     for white_team_id in 0..NB_LEARNING_RATES {
@@ -140,7 +195,7 @@ fn keep_best_learning_rate(all_training_results: Vec<TrainingResult>) -> Trainin
                 nn_is_sync => all_training_results.clone(),
                 not(nn_is_sync) => all_training_results.deref().to_owned(),
             };
-            handle.push(thread::spawn(move || {
+            handle.push(spawn_seeded(rng, move |rng| {
                 let mut res = Vec::new();
                 for white_nn in 0..NB_NEURAL_NETWORKS {
                     for black_nn in 0..NB_NEURAL_NETWORKS {
@@ -148,6 +203,7 @@ fn keep_best_learning_rate(all_training_results: Vec<TrainingResult>) -> Trainin
                             &all_training_results[white_team_id].neural_networks[white_nn],
                             &all_training_results[black_team_id].neural_networks[black_nn],
                             DEPTH_LIMIT_STRATEGY,
+                            rng,
                         );
                         res.push((white_team_id, black_team_id, game_result));
                     }
@@ -187,6 +243,7 @@ fn keep_best_learning_rate(all_training_results: Vec<TrainingResult>) -> Trainin
 fn train_neural_networks(
     neural_networks: Vec<NeuralNetwork>,
     duration: Duration,
+    rng: &mut Rng,
 ) -> TrainingResult {
     let mut neural_networks = neural_networks;
     let t0 = Instant::now();
@@ -200,9 +257,9 @@ fn train_neural_networks(
                 nn_is_sync => neural_networks_arc.clone(),
                 not(nn_is_sync) => neural_networks_arc.deref().to_owned(),
             };
-            handle.push(thread::spawn(move || {
+            handle.push(spawn_seeded(rng, move |rng| {
                 let (boards_played_white, boards_played_black, win_status) =
-                    play_game_return_boards(&neural_networks[white], &neural_networks[black]);
+                    play_game_return_boards(&neural_networks[white], &neural_networks[black], rng);
 
                 let (white_result, black_result) = get_score_from_win_status(win_status);
 
@@ -248,7 +305,12 @@ fn train_neural_networks(
 fn play_game_return_boards(
     nn_white: &NeuralNetwork,
     nn_black: &NeuralNetwork,
+    rng: &mut Rng,
 ) -> (Vec<Matrix>, Vec<Matrix>, WinStatus) {
+    // Unused by the training and PUCT strategies, which do not use alpha-beta
+    let transposition_table = Arc::new(TranspositionTable::new(0));
+    let (mut white_heuristic, mut black_heuristic) = (None, None);
+    let (mut white_tree, mut black_tree) = (None, None);
     let mut board = Board::new();
     let mut boards_played_white = Vec::new();
     let mut boards_played_black = Vec::new();
@@ -256,12 +318,30 @@ fn play_game_return_boards(
     while !board.is_end_game() {
         if white_plays {
             boards_played_white.push(input_of_board(&board));
-            let m = get_neural_network_move(&board, nn_white, Training, false);
-            board.play(&m);
+            let m = get_neural_network_move(
+                &board,
+                nn_white,
+                SELF_PLAY_STRATEGY,
+                false,
+                &transposition_table,
+                &mut white_heuristic,
+                &mut white_tree,
+                rng,
+            );
+            board.play(&m.best_move);
         } else {
             boards_played_black.push(input_of_board(&board));
-            let m = get_neural_network_move(&board, nn_black, Training, false);
-            board.play(&m);
+            let m = get_neural_network_move(
+                &board,
+                nn_black,
+                SELF_PLAY_STRATEGY,
+                false,
+                &transposition_table,
+                &mut black_heuristic,
+                &mut black_tree,
+                rng,
+            );
+            board.play(&m.best_move);
         }
         white_plays = !white_plays;
     }
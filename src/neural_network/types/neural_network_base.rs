@@ -152,7 +152,7 @@ fn sigmoid(z: &Array2<NeuralNetworkFloat>) -> Array2<NeuralNetworkFloat> {
     z.mapv(float_sigmoid)
 }
 
-fn float_sigmoid(x: NeuralNetworkFloat) -> NeuralNetworkFloat {
+pub fn float_sigmoid(x: NeuralNetworkFloat) -> NeuralNetworkFloat {
     1. / (1. + NeuralNetworkFloat::exp(-x))
 }
 
@@ -0,0 +1,203 @@
+use crate::checkers::board::Board;
+use crate::checkers::pdn::{board_of_fen, pdn_of_move};
+use crate::consts::{BENCH_DEPTH, TIME_PER_MOVE, TRANSPOSITION_TABLE_SIZE};
+use crate::neural_network::neural_network::generate_neural_network;
+use crate::players::alpha_beta::get_move::{
+    get_alpha_beta_move_depth_limit, get_alpha_beta_move_simple_heuristic_time_limit,
+    simple_heuristic,
+};
+use crate::players::alpha_beta::score::{NEG_INFINITY, POS_INFINITY};
+use crate::players::neural_network::get_move::{NeuralNetworkEvaluation, NeuralNetworkHeuristic};
+use crate::players::utils::alpha_beta::{
+    SearchLimits, SearchParameters, StopCondition, alpha_beta_moves_list,
+};
+use crate::players::utils::opening_book::BookChoice;
+use crate::players::utils::transposition_table::TranspositionTable;
+use crate::random::Rng;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+// Searches a fixed set of positions at BENCH_DEPTH, single-threaded, and reports the number of explored nodes.
+// Then, searches them during TIME_PER_MOVE, and reports the depth reached.
+// Each position is reached from the initial board by playing the moves of the given indexes in `Board::possible_moves`.
+// Then, searches tactical positions at TACTICS_DEPTH, and reports which ones are solved.
+// Finally, searches the positions at NN_BENCH_DEPTH with the neural network heuristic, for each way of scoring the boards
+// (cf `NeuralNetworkEvaluation`), and reports the durations. The network is untrained: only its layers matter here.
+
+const BENCH_POSITIONS: &[&[usize]] = &[
+    &[],
+    &[0, 0],
+    &[2, 5, 1],
+    &[6, 3, 0, 4, 2],
+    &[1, 1, 4, 2, 3, 0, 5],
+    &[3, 6, 2, 2, 5, 1, 0, 3],
+    &[4, 0, 3, 5, 1, 6, 2, 0, 4, 1],
+    &[5, 2, 6, 1, 0, 3, 4, 2, 1, 5, 3, 0],
+];
+/// Size (in MiB) of the transposition table used for each position
+const BENCH_TRANSPOSITION_TABLE_SIZE: usize = 16;
+/// Positions (as PDN FENs, cf `board_of_fen`) where a single move wins (or saves) material, found by deeper searches
+pub const TACTICAL_POSITIONS: &[(&str, &str)] = &[
+    ("B:W13,15,16,17,25,26,27,28,32:B2,4,5,6,7,8,9", "8-11"),
+    (
+        "B:W14,15,22,25,26,27,28,29,30,32:B1,3,4,5,6,7,8,13,20",
+        "8-12",
+    ),
+    ("W:W7,12,13,20,25,26,29,30,31,32:B2,3,4,5,6,8,17", "25-21"),
+    ("W:WK1,18,21,27,28,29,30,32:B11,20,22", "18-15"),
+    ("B:W18,20,23,28,29,32:B1,2,3,4,7,12,16,K30", "3-8"),
+    ("W:W6,13,21,22,24,29,30:B4,5,7,9,11,14", "6-1"),
+];
+/// Search depth of each tactical position
+pub const TACTICS_DEPTH: i8 = 4;
+/// Search depth of each position with the neural network heuristic
+const NN_BENCH_DEPTH: i8 = 6;
+
+pub fn run_bench(
+    parameters: SearchParameters,
+    baseline_parameters: SearchParameters,
+    rng: &mut Rng,
+) {
+    println!("{:?} vs {:?}", parameters, baseline_parameters);
+    println!("Depth {}", BENCH_DEPTH);
+    let (mut total_nodes, mut total_baseline_nodes) = (0, 0);
+    for (index, moves) in BENCH_POSITIONS.iter().enumerate() {
+        let board = board_of_moves(moves);
+        let (baseline_nodes, baseline_duration) = count_nodes(&board, baseline_parameters, rng);
+        let (nodes, duration) = count_nodes(&board, parameters, rng);
+        println!(
+            "Position {}: {} nodes in {:?} (baseline: {} nodes in {:?})",
+            index, nodes, duration, baseline_nodes, baseline_duration
+        );
+        total_nodes += nodes;
+        total_baseline_nodes += baseline_nodes;
+    }
+    println!(
+        "Total: {} nodes (baseline: {} nodes)",
+        total_nodes, total_baseline_nodes
+    );
+
+    println!("Time per move: {:?}", TIME_PER_MOVE);
+    let (mut total_depth, mut total_baseline_depth) = (0, 0);
+    for (index, moves) in BENCH_POSITIONS.iter().enumerate() {
+        let board = board_of_moves(moves);
+        let baseline_depth = reached_depth(&board, baseline_parameters, rng);
+        let depth = reached_depth(&board, parameters, rng);
+        println!(
+            "Position {}: depth {} (baseline: depth {})",
+            index, depth, baseline_depth
+        );
+        total_depth += depth as u64;
+        total_baseline_depth += baseline_depth as u64;
+    }
+    println!(
+        "Total: depth {} (baseline: depth {})",
+        total_depth, total_baseline_depth
+    );
+
+    println!("Tactics at depth {}", TACTICS_DEPTH);
+    let (mut total_solved, mut total_baseline_solved) = (0, 0);
+    for (index, (fen, winning_move)) in TACTICAL_POSITIONS.iter().enumerate() {
+        let solved = solves_tactic(fen, winning_move, parameters);
+        let baseline_solved = solves_tactic(fen, winning_move, baseline_parameters);
+        println!(
+            "Position {}: {} (baseline: {})",
+            index,
+            if solved { "solved" } else { "missed" },
+            if baseline_solved { "solved" } else { "missed" }
+        );
+        total_solved += usize::from(solved);
+        total_baseline_solved += usize::from(baseline_solved);
+    }
+    println!(
+        "Total: {}/{} solved (baseline: {}/{} solved)",
+        total_solved,
+        TACTICAL_POSITIONS.len(),
+        total_baseline_solved,
+        TACTICAL_POSITIONS.len()
+    );
+
+    println!("Neural network at depth {}", NN_BENCH_DEPTH);
+    let neural_network = generate_neural_network(rng);
+    for evaluation in [
+        NeuralNetworkEvaluation::Board,
+        NeuralNetworkEvaluation::BatchedLeaves,
+        NeuralNetworkEvaluation::Accumulator,
+        NeuralNetworkEvaluation::Quantized,
+    ] {
+        let heuristic = Arc::new(NeuralNetworkHeuristic::new(&neural_network, evaluation));
+        let (mut total_nodes, mut total_duration) = (0, Duration::ZERO);
+        for moves in BENCH_POSITIONS {
+            let t0 = Instant::now();
+            let search_result = get_alpha_beta_move_depth_limit(
+                &board_of_moves(moves),
+                &heuristic,
+                parameters,
+                NN_BENCH_DEPTH,
+                false,
+                &Arc::new(TranspositionTable::new(BENCH_TRANSPOSITION_TABLE_SIZE)),
+                rng,
+            );
+            total_nodes += search_result.nodes;
+            total_duration += t0.elapsed();
+        }
+        println!(
+            "{:?}: {} nodes in {:?}",
+            evaluation, total_nodes, total_duration
+        );
+    }
+}
+
+/// A tactical position is solved when its winning move is the only best move found at `TACTICS_DEPTH`
+pub fn solves_tactic(fen: &str, winning_move: &str, parameters: SearchParameters) -> bool {
+    let board = board_of_fen(fen).unwrap();
+    let (best_moves_opt, _) = alpha_beta_moves_list(
+        &board,
+        &Arc::new(simple_heuristic),
+        parameters,
+        TACTICS_DEPTH,
+        (NEG_INFINITY, POS_INFINITY),
+        false,
+        &StopCondition::new(SearchLimits::default()),
+        &TranspositionTable::new(BENCH_TRANSPOSITION_TABLE_SIZE),
+        &mut Vec::new(),
+    );
+    let (best_moves, _) = best_moves_opt.unwrap();
+    best_moves.iter().map(pdn_of_move).eq([winning_move])
+}
+
+fn board_of_moves(moves: &[usize]) -> Board {
+    let mut board = Board::new();
+    for &index in moves {
+        let mut possible_moves = board.possible_moves();
+        board.play(&possible_moves.swap_remove(index % possible_moves.len()));
+    }
+    board
+}
+
+fn count_nodes(board: &Board, parameters: SearchParameters, rng: &mut Rng) -> (u64, Duration) {
+    let t0 = Instant::now();
+    let search_result = get_alpha_beta_move_depth_limit(
+        board,
+        &Arc::new(simple_heuristic),
+        parameters,
+        BENCH_DEPTH,
+        false,
+        &Arc::new(TranspositionTable::new(BENCH_TRANSPOSITION_TABLE_SIZE)),
+        rng,
+    );
+    (search_result.nodes, t0.elapsed())
+}
+
+fn reached_depth(board: &Board, parameters: SearchParameters, rng: &mut Rng) -> i8 {
+    let search_result = get_alpha_beta_move_simple_heuristic_time_limit(
+        board,
+        parameters,
+        TIME_PER_MOVE,
+        BookChoice::Disabled,
+        true,
+        &Arc::new(TranspositionTable::new(TRANSPOSITION_TABLE_SIZE)),
+        rng,
+    );
+    search_result.principal_variations.last().unwrap().depth
+}
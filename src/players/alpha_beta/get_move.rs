@@ -1,68 +1,270 @@
 use crate::checkers::board::{Board, Move};
-use crate::players::alpha_beta::score::{BLACK_SIGN, WHITE_SIGN, naive_score};
-use crate::players::utils::alpha_beta::alpha_beta_moves_list;
+use crate::players::alpha_beta::score::{
+    BLACK_SIGN, NEG_INFINITY, POS_INFINITY, WHITE_SIGN, naive_score, win_distance,
+};
+use crate::players::utils::alpha_beta::{
+    SearchCounters, SearchInfo, SearchLimits, SearchParameters, SearchResult, StopCondition,
+    alpha_beta_moves_list, principal_variation,
+};
+use crate::players::utils::heuristic::Heuristic;
+use crate::players::utils::opening_book::{BookChoice, book_search_result};
+use crate::players::utils::tablebase::{tablebase_best_moves, tablebase_principal_variation};
+use crate::players::utils::time_control::TimeBudget;
+use crate::players::utils::transposition_table::TranspositionTable;
+use crate::random::Rng;
+use rand::RngExt;
 use std::sync::Arc;
-use std::sync::atomic::{AtomicBool, Ordering};
-use std::thread;
-use std::thread::sleep;
-use std::time::Duration;
+use std::time::{Duration, Instant};
+
+/// The aspiration window margin is multiplied by this factor after each failure
+const ASPIRATION_WINDOW_GROWTH: i64 = 4;
 
 pub fn get_alpha_beta_move_simple_heuristic_time_limit(
     board: &Board,
+    parameters: SearchParameters,
     duration: Duration,
+    book: BookChoice,
     threaded: bool,
-) -> Move {
-    get_alpha_beta_move_time_limit(board, Arc::new(simple_heuristic), duration, threaded)
+    transposition_table: &Arc<TranspositionTable>,
+    rng: &mut Rng,
+) -> SearchResult {
+    get_alpha_beta_move_time_limit(
+        board,
+        &Arc::new(simple_heuristic),
+        parameters,
+        TimeBudget::fixed(duration),
+        book,
+        threaded,
+        transposition_table,
+        rng,
+        |_| (),
+    )
 }
 
 pub fn get_alpha_beta_move_depth_limit(
     board: &Board,
     // TODO: Use a pub type `Arc<...>` everywhere when being part of stable Rust
-    #[cfg(nn_is_sync)] heuristic: Arc<impl Fn(&Board) -> i64 + Send + Sync + 'static>,
-    #[cfg(not(nn_is_sync))] heuristic: Arc<impl Fn(&Board) -> i64 + Send + Clone + 'static>,
+    #[cfg(nn_is_sync)] heuristic: &Arc<impl Heuristic + Send + Sync + 'static>,
+    #[cfg(not(nn_is_sync))] heuristic: &Arc<impl Heuristic + Send + Clone + 'static>,
+    parameters: SearchParameters,
     max_depth: i8,
     threaded: bool,
-) -> Move {
-    let cancel_search = Arc::new(AtomicBool::new(false));
-    let best_moves =
-        alpha_beta_moves_list(board, heuristic, max_depth, threaded, cancel_search).unwrap();
-    let i = rand::random_range(0..best_moves.len());
-    best_moves[i].clone()
+    transposition_table: &Arc<TranspositionTable>,
+    rng: &mut Rng,
+) -> SearchResult {
+    transposition_table.new_search();
+    let (best_moves_opt, counters) = alpha_beta_moves_list(
+        board,
+        heuristic,
+        parameters,
+        max_depth,
+        (NEG_INFINITY, POS_INFINITY),
+        threaded,
+        &StopCondition::new(SearchLimits::default()),
+        transposition_table,
+        &mut Vec::new(),
+    );
+    let (best_moves, score) = best_moves_opt.unwrap();
+    let best_move = choose_move(&best_moves, rng);
+    let principal_variation = principal_variation(
+        board,
+        &best_move,
+        parameters,
+        transposition_table,
+        max_depth,
+        score,
+    );
+    SearchResult {
+        best_move,
+        principal_variations: vec![principal_variation],
+        nodes: counters.nodes,
+    }
 }
 
+/// A book move (cf `probe_book`) is played without search.
+/// `on_search_info` is called after each completed iteration of the iterative deepening
+#[allow(clippy::too_many_arguments)]
 pub fn get_alpha_beta_move_time_limit(
     board: &Board,
-    #[cfg(nn_is_sync)] heuristic: Arc<impl Fn(&Board) -> i64 + Send + Sync + 'static>,
-    #[cfg(not(nn_is_sync))] heuristic: Arc<impl Fn(&Board) -> i64 + Send + Clone + 'static>,
-    duration: Duration,
+    #[cfg(nn_is_sync)] heuristic: &Arc<impl Heuristic + Send + Sync + 'static>,
+    #[cfg(not(nn_is_sync))] heuristic: &Arc<impl Heuristic + Send + Clone + 'static>,
+    parameters: SearchParameters,
+    budget: TimeBudget,
+    book: BookChoice,
     threaded: bool,
-) -> Move {
-    let mut best_moves = Vec::new();
-    let mut depth = 2 * 1;
-    let cancel_search = Arc::new(AtomicBool::new(false));
-    {
-        let cancel_search = cancel_search.clone();
-        thread::spawn(move || {
-            sleep(duration);
-            cancel_search.store(true, Ordering::Release);
-        });
+    transposition_table: &Arc<TranspositionTable>,
+    rng: &mut Rng,
+    on_search_info: impl FnMut(&SearchInfo),
+) -> SearchResult {
+    if let Some(search_result) = book_search_result(board, book, rng) {
+        return search_result;
     }
-    while !cancel_search.load(Ordering::Acquire) {
-        let heuristic = heuristic.clone();
-        let cancel_search = cancel_search.clone();
-        let new_best_moves_opt =
-            alpha_beta_moves_list(board, heuristic, depth, threaded, cancel_search);
-        if let Some(new_best_moves) = new_best_moves_opt {
-            best_moves = new_best_moves;
-            depth += 2;
+    let limits = SearchLimits {
+        time: Some(budget),
+        ..SearchLimits::default()
+    };
+    get_alpha_beta_move_with_limits(
+        board,
+        heuristic,
+        parameters,
+        limits,
+        threaded,
+        &StopCondition::new(limits),
+        transposition_table,
+        rng,
+        on_search_info,
+    )
+}
+
+/// Iterative deepening until a limit is reached, or until `stop_condition` is stopped (cf `Searcher`).
+/// `stop_condition` must have been created with `limits`, but its time limit may start later (cf `StopCondition::start_clock`).
+/// `on_search_info` is called after each completed iteration.
+/// If no iteration is completed, the move is chosen without search (cf `fallback_move`)
+#[allow(clippy::too_many_arguments)]
+pub fn get_alpha_beta_move_with_limits(
+    board: &Board,
+    #[cfg(nn_is_sync)] heuristic: &Arc<impl Heuristic + Send + Sync + 'static>,
+    #[cfg(not(nn_is_sync))] heuristic: &Arc<impl Heuristic + Send + Clone + 'static>,
+    parameters: SearchParameters,
+    limits: SearchLimits,
+    threaded: bool,
+    stop_condition: &StopCondition,
+    transposition_table: &Arc<TranspositionTable>,
+    rng: &mut Rng,
+    mut on_search_info: impl FnMut(&SearchInfo),
+) -> SearchResult {
+    let t0 = Instant::now();
+    if let Some(search_result) = tablebase_search_result(board, rng) {
+        return search_result;
+    }
+
+    transposition_table.new_search();
+    let mut best_move = Vec::new();
+    let mut principal_variations = Vec::new();
+    let mut counters = SearchCounters::default();
+    let mut depth = 2 * 1;
+    let mut window = (NEG_INFINITY, POS_INFINITY);
+    let mut window_margin = parameters.aspiration_window;
+    let is_only_move = board.possible_moves().len() == 1;
+    let mut nb_stable_iterations = 0;
+    let mut previous_score = None;
+    let mut move_orderings = Vec::new();
+    while !stop_condition.is_stopped() {
+        let (new_best_moves_opt, iteration_counters) = alpha_beta_moves_list(
+            board,
+            heuristic,
+            parameters,
+            depth,
+            window,
+            threaded,
+            stop_condition,
+            transposition_table,
+            &mut move_orderings,
+        );
+        counters += iteration_counters;
+        if let Some((new_best_moves, score)) = new_best_moves_opt {
+            // ********** Aspiration window failure: search again with a wider window **********
+            if score <= window.0 && window.0 > NEG_INFINITY {
+                window.0 = i64::max(score - window_margin, NEG_INFINITY);
+                window_margin *= ASPIRATION_WINDOW_GROWTH;
+                continue;
+            }
+            if score >= window.1 && window.1 < POS_INFINITY {
+                window.1 = i64::min(score + window_margin, POS_INFINITY);
+                window_margin *= ASPIRATION_WINDOW_GROWTH;
+                continue;
+            }
+            if parameters.aspiration_window > 0 {
+                window_margin = parameters.aspiration_window;
+                window = (
+                    i64::max(score - window_margin, NEG_INFINITY),
+                    i64::min(score + window_margin, POS_INFINITY),
+                );
+            }
+
+            if new_best_moves.contains(&best_move) {
+                nb_stable_iterations += 1;
+            } else {
+                nb_stable_iterations = 0;
+            }
+            let score_drop = previous_score.map_or(0, |previous_score| previous_score - score);
+            previous_score = Some(score);
+            best_move = choose_move(&new_best_moves, rng);
+            let principal_variation = principal_variation(
+                board,
+                &best_move,
+                parameters,
+                transposition_table,
+                depth,
+                score,
+            );
+            on_search_info(&SearchInfo {
+                principal_variation: principal_variation.clone(),
+                counters,
+                elapsed: t0.elapsed(),
+            });
+            principal_variations.push(principal_variation);
+            if is_only_move {
+                break;
+            }
+            if let Some(distance) = win_distance(score)
+                && distance <= depth as i64
+            {
+                // Every shorter win (or longer defense) has been explored: deeper searches cannot improve it
+                break;
+            }
+            if let Some((elapsed, budget)) = stop_condition.clock()
+                && elapsed >= budget.soft_limit(nb_stable_iterations, score_drop)
+            {
+                // The next iteration would probably not be completed in time
+                break;
+            }
+            if limits.depth.is_some_and(|max_depth| depth >= max_depth) {
+                break;
+            }
+            depth = limits
+                .depth
+                .map_or(depth + 2, |max_depth| i8::min(depth + 2, max_depth));
             if depth >= 2 * 50 {
-                // Probably end of game, or only 1 move allowed
+                // Probably end of game
                 break;
             }
         }
     }
-    let i = rand::random_range(0..best_moves.len());
-    best_moves[i].clone()
+    if principal_variations.is_empty() {
+        best_move = fallback_move(board, parameters, transposition_table);
+    }
+    SearchResult {
+        best_move,
+        principal_variations,
+        nodes: counters.nodes,
+    }
+}
+
+/// Search result of a tablebase move (cf `tablebase_best_moves`), if the board is in the tablebase
+fn tablebase_search_result(board: &Board, rng: &mut Rng) -> Option<SearchResult> {
+    let best_move = choose_move(&tablebase_best_moves(board)?, rng);
+    let principal_variation = tablebase_principal_variation(board, &best_move);
+    Some(SearchResult {
+        best_move,
+        principal_variations: vec![principal_variation],
+        nodes: 0,
+    })
+}
+
+/// Best move stored in the transposition table if it is legal, the first legal move otherwise
+fn fallback_move(
+    board: &Board,
+    parameters: SearchParameters,
+    transposition_table: &TranspositionTable,
+) -> Move {
+    let mut possible_moves = board.possible_moves();
+    let index = transposition_table
+        .get(parameters.transposition_key(board, board))
+        .and_then(|entry| entry.best_move)
+        .filter(|&index| index < possible_moves.len())
+        .unwrap_or(0);
+    possible_moves.swap_remove(index)
 }
 
 pub fn simple_heuristic(board: &Board) -> i64 {
@@ -73,3 +275,62 @@ pub fn simple_heuristic(board: &Board) -> i64 {
         BLACK_SIGN
     }) * naive_score(board)
 }
+
+fn choose_move(best_moves: &[Move], rng: &mut Rng) -> Move {
+    let i = rng.random_range(0..best_moves.len());
+    best_moves[i].clone()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        get_alpha_beta_move_time_limit, get_alpha_beta_move_with_limits, simple_heuristic,
+    };
+    use crate::checkers::board::Board;
+    use crate::consts::SEARCH_PARAMETERS;
+    use crate::players::utils::alpha_beta::{SearchLimits, StopCondition};
+    use crate::players::utils::opening_book::BookChoice;
+    use crate::players::utils::time_control::TimeBudget;
+    use crate::players::utils::transposition_table::TranspositionTable;
+    use crate::random::seeded_rng;
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    #[test]
+    fn zero_budget_plays_legal_move() {
+        let board = Board::new();
+        let search_result = get_alpha_beta_move_time_limit(
+            &board,
+            &Arc::new(simple_heuristic),
+            SEARCH_PARAMETERS,
+            TimeBudget::fixed(Duration::ZERO),
+            BookChoice::Disabled,
+            false,
+            &Arc::new(TranspositionTable::new(1)),
+            &mut seeded_rng(0),
+            |_| (),
+        );
+        assert!(board.possible_moves().contains(&search_result.best_move));
+    }
+
+    #[test]
+    fn stopped_search_plays_legal_move() {
+        let board = Board::new();
+        let limits = SearchLimits::default();
+        let stop_condition = StopCondition::new(limits);
+        stop_condition.stop();
+        let search_result = get_alpha_beta_move_with_limits(
+            &board,
+            &Arc::new(simple_heuristic),
+            SEARCH_PARAMETERS,
+            limits,
+            false,
+            &stop_condition,
+            &Arc::new(TranspositionTable::new(1)),
+            &mut seeded_rng(0),
+            |_| (),
+        );
+        assert!(search_result.principal_variations.is_empty());
+        assert!(board.possible_moves().contains(&search_result.best_move));
+    }
+}
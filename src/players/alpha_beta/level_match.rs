@@ -0,0 +1,57 @@
+use crate::checkers::board::{Board, Move};
+use crate::consts::{
+    LEVEL_MATCH_TIME_PER_MOVE, SEARCH_PARAMETERS, STRENGTH_LEVELS, TRANSPOSITION_TABLE_SIZE,
+};
+use crate::players::alpha_beta::get_move::simple_heuristic;
+use crate::players::alpha_beta::search_match::run_match;
+use crate::players::alpha_beta::strength::{StrengthLevel, get_alpha_beta_move_with_level};
+use crate::players::utils::time_control::TimeBudget;
+use crate::players::utils::transposition_table::TranspositionTable;
+use crate::random::{Rng, seeded_rng};
+use rand::RngExt;
+use std::sync::Arc;
+
+// Plays each strength level against the level below, both with the simple heuristic and the limits of their level:
+// `LEVEL_MATCH_TIME_PER_MOVE` per move, capped by the maximum time of the level (cf `StrengthLevel::max_time`).
+
+pub fn run_level_match(rng: &mut Rng) {
+    for levels in STRENGTH_LEVELS.windows(2) {
+        let [(weaker_name, weaker_level), (name, level)] = levels else {
+            unreachable!()
+        };
+        println!("{} vs {}", name, weaker_name);
+        let (wins, draws, losses) = run_match(
+            |seed| level_player(*level, seed),
+            |seed| level_player(*weaker_level, seed),
+            rng,
+        );
+        let score = (wins as f64 + draws as f64 / 2.) / (wins + draws + losses) as f64;
+        println!(
+            "{} scores {:.0}% against {}",
+            name,
+            100. * score,
+            weaker_name
+        );
+    }
+}
+
+fn level_player(level: StrengthLevel, seed: u64) -> impl FnMut(&Board) -> Move {
+    let transposition_table = Arc::new(TranspositionTable::new(TRANSPOSITION_TABLE_SIZE));
+    let mut rng = seeded_rng(seed);
+    let noise_salt = rng.random();
+    move |board| {
+        get_alpha_beta_move_with_level(
+            board,
+            &Arc::new(simple_heuristic),
+            SEARCH_PARAMETERS,
+            level,
+            TimeBudget::fixed(LEVEL_MATCH_TIME_PER_MOVE),
+            noise_salt,
+            true,
+            &transposition_table,
+            &mut rng,
+            |_| (),
+        )
+        .best_move
+    }
+}
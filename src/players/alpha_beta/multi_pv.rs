@@ -0,0 +1,62 @@
+use crate::checkers::board::Board;
+use crate::players::utils::alpha_beta::{
+    PrincipalVariation, SearchLimits, SearchParameters, StopCondition, multi_pv_moves_list,
+    principal_variation,
+};
+use crate::players::utils::heuristic::Heuristic;
+use crate::players::utils::move_ordering::MoveOrdering;
+use crate::players::utils::transposition_table::TranspositionTable;
+
+/// The `nb_lines` best moves with their exact score and principal variation, best first.
+/// Iterative deepening: the lines of the deepest completed iteration are returned (none if the first one is not completed).
+pub fn multi_pv(
+    board: &Board,
+    heuristic: &impl Heuristic,
+    parameters: SearchParameters,
+    nb_lines: usize,
+    limits: SearchLimits,
+    transposition_table: &TranspositionTable,
+) -> Vec<PrincipalVariation> {
+    if board.is_end_game() {
+        return Vec::new();
+    }
+
+    transposition_table.new_search();
+    let stop_condition = StopCondition::new(limits);
+    let max_depth = limits.depth.unwrap_or(2 * 50);
+
+    let mut lines = Vec::new();
+    let mut move_ordering = MoveOrdering::new();
+    let mut depth = i8::min(2, max_depth);
+    loop {
+        let previous_moves = lines
+            .iter()
+            .map(|line: &PrincipalVariation| line.moves[0].clone())
+            .collect::<Vec<_>>();
+        let (new_lines_opt, _) = multi_pv_moves_list(
+            board,
+            heuristic,
+            parameters,
+            depth,
+            nb_lines,
+            &previous_moves,
+            &stop_condition,
+            transposition_table,
+            &mut move_ordering,
+        );
+        let Some(new_lines) = new_lines_opt else {
+            break;
+        };
+        lines = new_lines
+            .into_iter()
+            .map(|(m, score)| {
+                principal_variation(board, &m, parameters, transposition_table, depth, score)
+            })
+            .collect();
+        if depth >= max_depth {
+            break;
+        }
+        depth = i8::min(depth + 2, max_depth);
+    }
+    lines
+}
@@ -0,0 +1,94 @@
+use crate::checkers::board::Board;
+use crate::players::alpha_beta::searcher::Searcher;
+use crate::players::utils::alpha_beta::{SearchLimits, SearchParameters, SearchResult};
+use crate::players::utils::heuristic::Heuristic;
+use crate::players::utils::time_control::TimeBudget;
+use crate::players::utils::transposition_table::TranspositionTable;
+use std::sync::Arc;
+
+// Pondering: during the opponent's turn, the AI searches the board reached by the expected opponent move, without time limit.
+// On a ponder hit, this search goes on as the search of the AI move, with the time budget of the AI clock starting then:
+// the time spent pondering is saved. On a miss, it is cancelled, but the transposition table got warmer.
+
+pub struct Ponder {
+    /// Board expected after the opponent move
+    expected_board: Board,
+    searcher: Searcher,
+}
+
+impl Ponder {
+    pub fn start(
+        expected_board: Board,
+        #[cfg(nn_is_sync)] heuristic: &Arc<impl Heuristic + Send + Sync + 'static>,
+        #[cfg(not(nn_is_sync))] heuristic: &Arc<impl Heuristic + Send + Clone + 'static>,
+        parameters: SearchParameters,
+        transposition_table: &Arc<TranspositionTable>,
+        seed: u64,
+    ) -> Self {
+        let searcher = Searcher::start(
+            expected_board.clone(),
+            heuristic,
+            parameters,
+            SearchLimits::default(),
+            true,
+            transposition_table,
+            seed,
+            |_| (),
+        );
+        Ponder {
+            expected_board,
+            searcher,
+        }
+    }
+
+    /// `board` is the board after the opponent move
+    pub fn is_hit(&self, board: &Board) -> bool {
+        board.state_hash() == self.expected_board.state_hash()
+    }
+
+    /// On a ponder hit, the search goes on until the soft limit of `budget` (cf `TimeBudget::soft_limit`), starting now
+    pub fn hit(self, budget: TimeBudget) -> SearchResult {
+        self.searcher.start_clock(budget);
+        self.searcher.wait()
+    }
+
+    /// On a ponder miss, the search is cancelled
+    pub fn stop(self) {
+        self.searcher.stop();
+        self.searcher.wait();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Ponder;
+    use crate::checkers::board::Board;
+    use crate::consts::SEARCH_PARAMETERS;
+    use crate::players::alpha_beta::get_move::simple_heuristic;
+    use crate::players::utils::time_control::TimeBudget;
+    use crate::players::utils::transposition_table::TranspositionTable;
+    use std::sync::Arc;
+    use std::thread::sleep;
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn ponder_hit_goes_on_within_budget() {
+        let board = Board::new();
+        let ponder = Ponder::start(
+            board.clone(),
+            &Arc::new(simple_heuristic),
+            SEARCH_PARAMETERS,
+            &Arc::new(TranspositionTable::new(16)),
+            0,
+        );
+        sleep(Duration::from_millis(200));
+        assert!(ponder.is_hit(&board));
+        let budget = TimeBudget::fixed(Duration::from_millis(100));
+        let t0 = Instant::now();
+        let search_result = ponder.hit(budget);
+        // The time spent pondering is not counted: the search may go past its budget by one check of its nodes only
+        assert!(t0.elapsed() < Duration::from_secs(1));
+        assert!(!search_result.principal_variations.is_empty());
+        assert!(board.possible_moves().contains(&search_result.best_move));
+    }
+}
@@ -14,9 +14,11 @@ const MAX_SCORE_COEF: i64 = MAX_BOARD_COUNT_SCORE_COEF * MAX_MOVES_WITHOUT_CAPTU
 const MAX_SCORE_WITHOUT_COEF: i64 = (NB_PLAYERS_LINES * BOARD_SIZE / 2) as i64 * MAX_PIECE_SCORE;
 pub const POS_INFINITY: i64 = MAX_SCORE_WITHOUT_COEF * MAX_SCORE_COEF + 1;
 pub const NEG_INFINITY: i64 = -POS_INFINITY;
-const WHITE_WIN: i64 = WHITE_SIGN * (POS_INFINITY - 1);
-const BLACK_WIN: i64 = BLACK_SIGN * (POS_INFINITY - 1);
-const DRAW: i64 = 0;
+const WHITE_WIN: i64 = WHITE_SIGN * win_score(0);
+const BLACK_WIN: i64 = BLACK_SIGN * win_score(0);
+pub const DRAW: i64 = 0;
+/// Win distances are at most `MAX_WIN_DISTANCE` plies, so that win scores are above every heuristic score
+const MAX_WIN_DISTANCE: i64 = 10_000;
 
 pub fn naive_score(board: &Board) -> i64 {
     match board.get_win_status() {
@@ -42,6 +44,68 @@ pub fn naive_score(board: &Board) -> i64 {
     score
 }
 
+/// Score of a won game for the current player, reached in `distance` plies
+pub const fn win_score(distance: i64) -> i64 {
+    POS_INFINITY - 1 - distance
+}
+
+/// Number of plies before the end of the game, if `score` is a win or loss score
+pub fn win_distance(score: i64) -> Option<i64> {
+    let distance = POS_INFINITY - 1 - score.abs();
+    (distance <= MAX_WIN_DISTANCE).then_some(distance)
+}
+
+/// Current player POV, for a game ended `ply` plies after the search root (cf `draw_score` for the draws)
+pub fn end_game_score(board: &Board, ply: usize, contempt: i64) -> i64 {
+    match board.get_win_status() {
+        Win(player) => {
+            if player.is_white() == board.get_player_is_white() {
+                win_score(ply as i64)
+            } else {
+                -win_score(ply as i64)
+            }
+        }
+        Draw => draw_score(contempt, ply),
+        Continue => panic!("Continue"),
+    }
+}
+
+/// Score of a draw for the player to move `ply` plies after the search root.
+/// The root player (the engine) scores it `-contempt`, so a positive contempt makes it avoid draws
+pub fn draw_score(contempt: i64, ply: usize) -> i64 {
+    if ply.is_multiple_of(2) {
+        DRAW - contempt
+    } else {
+        DRAW + contempt
+    }
+}
+
+/// Current player POV, added to the heuristic score of a board `ply` plies after the search root.
+/// The draw counters pull the heuristic scores toward 0 (cf `naive_score`): this pulls them toward the draw score instead
+pub fn contempt_score(board: &Board, ply: usize, contempt: i64) -> i64 {
+    let coef = coef_board_count(board.get_board_count())
+        * coef_moves_without_capture(board.get_moves_without_capture());
+    draw_score(contempt, ply) * (MAX_SCORE_COEF - coef) / MAX_SCORE_COEF
+}
+
+// Search scores count win distances from the search root, while cached scores count them from their own board
+
+/// Converts a score counted from the board `ply` plies after the root, into a score counted from the root
+pub fn root_score_of_board_score(score: i64, ply: usize) -> i64 {
+    match win_distance(score) {
+        Some(_) => score - score.signum() * ply as i64,
+        None => score,
+    }
+}
+
+/// Converts a score counted from the root, into a score counted from the board `ply` plies after the root
+pub fn board_score_of_root_score(score: i64, ply: usize) -> i64 {
+    match win_distance(score) {
+        Some(_) => score + score.signum() * ply as i64,
+        None => score,
+    }
+}
+
 fn piece_score(piece: Piece, x: i8, y: i8) -> i64 {
     let sign = if piece.is_white() {
         WHITE_SIGN
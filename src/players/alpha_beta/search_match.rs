@@ -0,0 +1,110 @@
+use crate::checkers::board::{Board, Move};
+use crate::checkers::win_status::WinStatus::{Continue, Draw, Win};
+use crate::consts::{
+    MATCH_NB_OPENINGS, MATCH_OPENING_LENGTH, MATCH_TIME_PER_MOVE, TRANSPOSITION_TABLE_SIZE,
+};
+use crate::players::alpha_beta::get_move::get_alpha_beta_move_simple_heuristic_time_limit;
+use crate::players::utils::alpha_beta::SearchParameters;
+use crate::players::utils::opening_book::BookChoice;
+use crate::players::utils::transposition_table::TranspositionTable;
+use crate::random::{Rng, seeded_rng};
+use rand::RngExt;
+use std::sync::Arc;
+
+// Plays the search using `parameters` against the search using `baseline_parameters`, both with the simple heuristic.
+// Each random opening is played twice, so that both players play it with each color.
+
+pub fn run_search_match(
+    parameters: SearchParameters,
+    baseline_parameters: SearchParameters,
+    rng: &mut Rng,
+) {
+    println!("{:?} vs {:?}", parameters, baseline_parameters);
+    run_match(
+        |seed| alpha_beta_player(parameters, seed),
+        |seed| alpha_beta_player(baseline_parameters, seed),
+        rng,
+    );
+}
+
+/// Plays a match between two players, and returns the wins, draws and losses of `player`.
+/// `new_player` and `new_baseline` are called before each game with a seed drawn from `rng`, so that players can keep a state
+/// (and their own RNG) during a game
+pub fn run_match<P: FnMut(&Board) -> Move, B: FnMut(&Board) -> Move>(
+    new_player: impl Fn(u64) -> P,
+    new_baseline: impl Fn(u64) -> B,
+    rng: &mut Rng,
+) -> (u32, u32, u32) {
+    let (mut wins, mut draws, mut losses) = (0, 0, 0);
+    for _ in 0..MATCH_NB_OPENINGS {
+        let opening = random_opening(rng);
+        for plays_white in [true, false] {
+            let player = new_player(rng.random());
+            let baseline = new_baseline(rng.random());
+            match play_game(&opening, player, baseline, plays_white) {
+                Some(true) => wins += 1,
+                None => draws += 1,
+                Some(false) => losses += 1,
+            }
+        }
+        println!("wins: {}, draws: {}, losses: {}", wins, draws, losses);
+    }
+    (wins, draws, losses)
+}
+
+/// Alpha-beta search with the simple heuristic and `MATCH_TIME_PER_MOVE` per move, whose random decisions depend on `seed`
+pub fn alpha_beta_player(parameters: SearchParameters, seed: u64) -> impl FnMut(&Board) -> Move {
+    let transposition_table = Arc::new(TranspositionTable::new(TRANSPOSITION_TABLE_SIZE));
+    let mut rng = seeded_rng(seed);
+    move |board| {
+        get_alpha_beta_move_simple_heuristic_time_limit(
+            board,
+            parameters,
+            MATCH_TIME_PER_MOVE,
+            BookChoice::Disabled,
+            true,
+            &transposition_table,
+            &mut rng,
+        )
+        .best_move
+    }
+}
+
+fn random_opening(rng: &mut Rng) -> Board {
+    let mut board = Board::new();
+    for _ in 0..MATCH_OPENING_LENGTH {
+        let mut possible_moves = board.possible_moves();
+        if possible_moves.is_empty() {
+            break;
+        }
+        let i = rng.random_range(0..possible_moves.len());
+        board.play(&possible_moves.swap_remove(i));
+    }
+    if board.is_end_game() {
+        return random_opening(rng);
+    }
+    board
+}
+
+/// Returns `Some(true)` if `player` wins, `None` on a draw
+fn play_game(
+    opening: &Board,
+    mut player: impl FnMut(&Board) -> Move,
+    mut baseline: impl FnMut(&Board) -> Move,
+    plays_white: bool,
+) -> Option<bool> {
+    let mut board = opening.clone();
+    while !board.is_end_game() {
+        let m = if board.get_player_is_white() == plays_white {
+            player(&board)
+        } else {
+            baseline(&board)
+        };
+        board.play(&m);
+    }
+    match board.get_win_status() {
+        Win(player) => Some(player.is_white() == plays_white),
+        Draw => None,
+        Continue => panic!("Continue"),
+    }
+}
@@ -0,0 +1,113 @@
+use crate::checkers::board::Board;
+use crate::players::alpha_beta::get_move::get_alpha_beta_move_with_limits;
+use crate::players::utils::alpha_beta::{
+    SearchInfo, SearchLimits, SearchParameters, SearchResult, StopCondition,
+};
+use crate::players::utils::heuristic::Heuristic;
+use crate::players::utils::time_control::TimeBudget;
+use crate::players::utils::transposition_table::TranspositionTable;
+use crate::random::seeded_rng;
+#[cfg(not(nn_is_sync))]
+use std::ops::Deref;
+use std::sync::Arc;
+use std::thread;
+use std::thread::JoinHandle;
+
+/// A search running in its own thread, until one of its limits is reached or until it is stopped
+pub struct Searcher {
+    stop_condition: Arc<StopCondition>,
+    handle: JoinHandle<SearchResult>,
+}
+
+impl Searcher {
+    /// `on_search_info` is called after each completed iteration of the iterative deepening.
+    /// The random decisions of the search depend on `seed`
+    #[allow(clippy::too_many_arguments)]
+    pub fn start(
+        board: Board,
+        #[cfg(nn_is_sync)] heuristic: &Arc<impl Heuristic + Send + Sync + 'static>,
+        #[cfg(not(nn_is_sync))] heuristic: &Arc<impl Heuristic + Send + Clone + 'static>,
+        parameters: SearchParameters,
+        limits: SearchLimits,
+        threaded: bool,
+        transposition_table: &Arc<TranspositionTable>,
+        seed: u64,
+        on_search_info: impl FnMut(&SearchInfo) + Send + 'static,
+    ) -> Self {
+        let stop_condition = Arc::new(StopCondition::new(limits));
+        let handle = {
+            let heuristic = cfg_select! {
+                nn_is_sync => heuristic.clone(),
+                not(nn_is_sync) => heuristic.deref().to_owned(),
+            };
+            let stop_condition = stop_condition.clone();
+            let transposition_table = transposition_table.clone();
+            thread::spawn(move || {
+                let heuristic = cfg_select! {
+                    nn_is_sync => heuristic,
+                    not(nn_is_sync) => Arc::new(heuristic),
+                };
+                get_alpha_beta_move_with_limits(
+                    &board,
+                    &heuristic,
+                    parameters,
+                    limits,
+                    threaded,
+                    &stop_condition,
+                    &transposition_table,
+                    &mut seeded_rng(seed),
+                    on_search_info,
+                )
+            })
+        };
+        Searcher {
+            stop_condition,
+            handle,
+        }
+    }
+
+    /// Starts now the time limit of a search started without one (cf `StopCondition::start_clock`)
+    pub fn start_clock(&self, budget: TimeBudget) {
+        self.stop_condition.start_clock(budget);
+    }
+
+    /// Stops the search as soon as possible: its result is the one of the deepest completed iteration,
+    /// or a legal move chosen without search if no iteration is completed (cf `get_alpha_beta_move_with_limits`)
+    pub fn stop(&self) {
+        self.stop_condition.stop();
+    }
+
+    /// Waits for the end of the search, and returns its result
+    pub fn wait(self) -> SearchResult {
+        self.handle.join().unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Searcher;
+    use crate::checkers::board::Board;
+    use crate::consts::SEARCH_PARAMETERS;
+    use crate::players::alpha_beta::get_move::simple_heuristic;
+    use crate::players::utils::alpha_beta::SearchLimits;
+    use crate::players::utils::transposition_table::TranspositionTable;
+    use std::sync::Arc;
+
+    #[test]
+    fn stopped_searcher_plays_legal_move() {
+        let board = Board::new();
+        let searcher = Searcher::start(
+            board.clone(),
+            &Arc::new(simple_heuristic),
+            SEARCH_PARAMETERS,
+            SearchLimits::default(),
+            false,
+            &Arc::new(TranspositionTable::new(1)),
+            0,
+            |_| (),
+        );
+        searcher.stop();
+        let search_result = searcher.wait();
+        assert!(board.possible_moves().contains(&search_result.best_move));
+    }
+}
@@ -0,0 +1,210 @@
+use crate::checkers::board::Board;
+use crate::players::alpha_beta::get_move::get_alpha_beta_move_with_limits;
+use crate::players::utils::alpha_beta::{
+    SearchInfo, SearchLimits, SearchParameters, SearchResult, StopCondition,
+};
+use crate::players::utils::heuristic::Heuristic;
+use crate::players::utils::time_control::TimeBudget;
+use crate::players::utils::transposition_table::TranspositionTable;
+use crate::random::Rng;
+use rand::RngExt;
+use std::ops::Deref;
+use std::sync::Arc;
+use std::time::Duration;
+
+// Strength levels weaken the AI for human opponents: shallower and shorter searches, a noisy heuristic, and random moves.
+// The levels are calibrated against each other by the `level_match` command.
+
+#[derive(Clone, Copy, Debug)]
+pub struct StrengthLevel {
+    pub max_depth: Option<i8>,
+    /// Maximum time per move, even if the clock allows more
+    pub max_time: Option<Duration>,
+    /// Each board score of the heuristic is shifted by up to this fraction of its scale (cf `Heuristic::score_scale`),
+    /// so that a level plays alike with every heuristic.
+    /// The shift of a board is fixed during a game, so that the search stays consistent with the transposition table
+    pub eval_noise: f64,
+    /// Probability of playing a random move instead of the searched one
+    pub blunder_probability: f64,
+}
+
+impl StrengthLevel {
+    pub const FULL: StrengthLevel = StrengthLevel {
+        max_depth: None,
+        max_time: None,
+        eval_noise: 0.,
+        blunder_probability: 0.,
+    };
+
+    pub fn is_full_strength(&self) -> bool {
+        self.max_depth.is_none()
+            && self.max_time.is_none()
+            && self.eval_noise == 0.
+            && self.blunder_probability == 0.
+    }
+}
+
+/// Same as `get_alpha_beta_move_time_limit`, weakened by `level`.
+/// The heuristic noise of a board depends on its state and on `noise_salt`, which must be fixed during a game:
+/// the noisy scores of the previous searches are kept in the transposition table.
+/// On a blunder, the best move is a random move, and the principal variations are still the ones of the search
+#[allow(clippy::too_many_arguments)]
+pub fn get_alpha_beta_move_with_level(
+    board: &Board,
+    #[cfg(nn_is_sync)] heuristic: &Arc<impl Heuristic + Send + Sync + 'static>,
+    #[cfg(not(nn_is_sync))] heuristic: &Arc<impl Heuristic + Send + Clone + 'static>,
+    parameters: SearchParameters,
+    level: StrengthLevel,
+    budget: TimeBudget,
+    noise_salt: u64,
+    threaded: bool,
+    transposition_table: &Arc<TranspositionTable>,
+    rng: &mut Rng,
+    on_search_info: impl FnMut(&SearchInfo),
+) -> SearchResult {
+    let limits = SearchLimits {
+        depth: level.max_depth,
+        time: Some(
+            level
+                .max_time
+                .map_or(budget, |max_time| budget.capped(max_time)),
+        ),
+        ..SearchLimits::default()
+    };
+    let stop_condition = StopCondition::new(limits);
+    let mut search_result = if level.eval_noise == 0. {
+        get_alpha_beta_move_with_limits(
+            board,
+            heuristic,
+            parameters,
+            limits,
+            threaded,
+            &stop_condition,
+            transposition_table,
+            rng,
+            on_search_info,
+        )
+    } else {
+        let noisy_heuristic = Arc::new(NoisyHeuristic {
+            heuristic: cfg_select! {
+                nn_is_sync => heuristic.clone(),
+                not(nn_is_sync) => Box::new(heuristic.deref().to_owned()),
+            },
+            noise: (level.eval_noise * heuristic.score_scale()) as i64,
+            noise_salt,
+        });
+        get_alpha_beta_move_with_limits(
+            board,
+            &noisy_heuristic,
+            parameters,
+            limits,
+            threaded,
+            &stop_condition,
+            transposition_table,
+            rng,
+            on_search_info,
+        )
+    };
+    if rng.random::<f64>() < level.blunder_probability {
+        let mut possible_moves = board.possible_moves();
+        let i = rng.random_range(0..possible_moves.len());
+        search_result.best_move = possible_moves.swap_remove(i);
+    }
+    search_result
+}
+
+/// `heuristic` with a shift of each board score (cf `StrengthLevel::eval_noise`).
+/// The state of `heuristic` is kept, so that the noise is added on top of its incremental and batched evaluations
+#[derive(Clone)]
+struct NoisyHeuristic<H> {
+    heuristic: H,
+    /// Maximum shift
+    noise: i64,
+    noise_salt: u64,
+}
+
+impl<H: Deref<Target: Heuristic>> NoisyHeuristic<H> {
+    fn shift(&self, board: &Board) -> i64 {
+        let noise_range = 2 * self.noise as u64 + 1;
+        ((board.state_hash() ^ self.noise_salt) % noise_range) as i64 - self.noise
+    }
+}
+
+impl<H: Deref<Target: Heuristic>> Heuristic for NoisyHeuristic<H> {
+    type State = <H::Target as Heuristic>::State;
+
+    fn score(&self, board: &Board) -> i64 {
+        self.heuristic.score(board) + self.shift(board)
+    }
+
+    fn state(&self, board: &Board) -> Self::State {
+        self.heuristic.state(board)
+    }
+
+    fn play(&self, state: &Self::State, board: &Board, child_board: &Board) -> Self::State {
+        self.heuristic.play(state, board, child_board)
+    }
+
+    fn score_with_state(&self, board: &Board, state: &Self::State) -> i64 {
+        self.heuristic.score_with_state(board, state) + self.shift(board)
+    }
+
+    fn scores(&self, boards: &[Board]) -> Vec<i64> {
+        self.heuristic
+            .scores(boards)
+            .into_iter()
+            .zip(boards)
+            .map(|(score, board)| score + self.shift(board))
+            .collect()
+    }
+
+    fn score_scale(&self) -> f64 {
+        self.heuristic.score_scale()
+    }
+
+    fn win_probability(&self, score: i64) -> f64 {
+        self.heuristic.win_probability(score)
+    }
+
+    fn is_batched(&self) -> bool {
+        self.heuristic.is_batched()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::NoisyHeuristic;
+    use crate::checkers::board::Board;
+    use crate::consts::NeuralNetwork;
+    use crate::neural_network::neural_network::generate_neural_networks;
+    use crate::players::neural_network::get_move::{
+        NeuralNetworkEvaluation, NeuralNetworkHeuristic,
+    };
+    use crate::players::utils::heuristic::Heuristic;
+    use crate::random::seeded_rng;
+    use std::sync::Arc;
+
+    #[test]
+    fn noise_keeps_heuristic_state() {
+        let neural_network: NeuralNetwork =
+            generate_neural_networks(&mut seeded_rng(0)).swap_remove(0);
+        let heuristic = Arc::new(NeuralNetworkHeuristic::new(
+            &neural_network,
+            NeuralNetworkEvaluation::Accumulator,
+        ));
+        let noisy_heuristic = NoisyHeuristic {
+            heuristic: heuristic.clone(),
+            noise: (0.5 * heuristic.score_scale()) as i64,
+            noise_salt: 0,
+        };
+        let board = Board::new();
+        let mut child_board = board.clone();
+        child_board.play(&board.possible_moves()[0]);
+        let state = noisy_heuristic.play(&noisy_heuristic.state(&board), &board, &child_board);
+        assert!(state.is_some());
+        let shift = noisy_heuristic.score_with_state(&child_board, &state)
+            - heuristic.score_with_state(&child_board, &state);
+        assert_eq!(shift, noisy_heuristic.shift(&child_board));
+        assert!(shift.abs() <= noisy_heuristic.noise);
+    }
+}
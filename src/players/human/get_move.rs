@@ -1,8 +1,10 @@
-use crate::checkers::board::{Board, Move, char_of_x, char_of_y};
-use crate::players::alpha_beta::get_move::{get_alpha_beta_move_depth_limit, simple_heuristic};
-use std::fmt::Write;
+use crate::checkers::board::{Board, Move, string_of_move};
+use crate::consts::{ANALYSIS_SEARCH_PARAMETERS, DEPTH_LIMIT_TRANSPOSITION_TABLE_SIZE};
+use crate::players::alpha_beta::get_move::simple_heuristic;
+use crate::players::alpha_beta::multi_pv::multi_pv;
+use crate::players::utils::alpha_beta::SearchLimits;
+use crate::players::utils::transposition_table::TranspositionTable;
 use std::io::stdin;
-use std::sync::Arc;
 
 pub fn get_human_move(board: &Board) -> Move {
     let possible_moves = board.possible_moves();
@@ -13,7 +15,20 @@ pub fn get_human_move(board: &Board) -> Move {
     print_moves_list(&possible_moves);
     println!("Which move do you want to play?");
     let mut good_move_index = 0;
-    let good_move = get_alpha_beta_move_depth_limit(board, Arc::new(simple_heuristic), 2, false);
+    let good_move = multi_pv(
+        board,
+        &simple_heuristic,
+        ANALYSIS_SEARCH_PARAMETERS,
+        1,
+        SearchLimits {
+            depth: Some(2),
+            ..SearchLimits::default()
+        },
+        &TranspositionTable::new(DEPTH_LIMIT_TRANSPOSITION_TABLE_SIZE),
+    )
+    .swap_remove(0)
+    .moves
+    .swap_remove(0);
     for i in 0..possible_moves.len() {
         if possible_moves[i] == good_move {
             good_move_index = i;
@@ -47,14 +62,3 @@ fn print_moves_list(moves: &[Move]) {
         println!("{}: {}", i, string_of_move(m));
     }
 }
-
-fn string_of_move(m: &Move) -> String {
-    let mut str = String::new();
-    for &(x, y) in m {
-        write!(str, "{}{} -> ", char_of_x(x), char_of_y(y)).unwrap();
-    }
-    for _ in 0.." -> ".len() {
-        str.pop();
-    }
-    str
-}
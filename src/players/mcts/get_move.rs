@@ -0,0 +1,181 @@
+use crate::checkers::board::Board;
+use crate::players::mcts::tree::{LeafEvaluation, MctsTree, end_game_value};
+use crate::players::utils::alpha_beta::{PrincipalVariation, SearchResult};
+use crate::players::utils::heuristic::Heuristic;
+use crate::players::utils::tablebase::{TablebaseResult, probe_tablebase};
+use crate::random::Rng;
+use rand::RngExt;
+use std::time::{Duration, Instant};
+
+#[derive(Clone, Copy, Debug)]
+pub enum Playout {
+    /// Random moves until the end of the game
+    Random,
+    /// The heuristic scores the leaf, as a win probability (cf `Heuristic::win_probability`)
+    Heuristic,
+}
+
+/// The search stops as soon as one of the limits is reached. At least one of them must be set
+#[derive(Clone, Copy, Debug, Default)]
+pub struct MctsLimits {
+    pub playouts: Option<u32>,
+    pub time: Option<Duration>,
+}
+
+/// `tree` is kept between the moves of a game, so that the subtree of the new board is reused (cf `MctsTree::set_root`).
+/// The playouts give no prior probabilities: `tree` should use the UCT selection.
+/// `rng` is used by random playouts
+pub fn get_mcts_move(
+    board: &Board,
+    heuristic: &impl Heuristic,
+    playout: Playout,
+    limits: MctsLimits,
+    tree: &mut MctsTree,
+    rng: &mut Rng,
+) -> SearchResult {
+    tree.set_root(board);
+    run_search(board, limits, tree, |board| LeafEvaluation {
+        // Tablebase results are from the current player POV
+        value: match probe_tablebase(board) {
+            Some(TablebaseResult::Win(_)) => 0.,
+            Some(TablebaseResult::Loss(_)) => 1.,
+            Some(TablebaseResult::Draw) => 0.5,
+            None => match playout {
+                Playout::Random => random_playout(board, rng),
+                Playout::Heuristic => 1. - heuristic.win_probability(heuristic.score(board)),
+            },
+        },
+        priors: None,
+    })
+}
+
+/// Runs iterations of `tree`, whose root must be `board`, until a limit is reached (cf `MctsTree::run_iteration`),
+/// and at least until a move of the root is visited.
+/// The score of the principal variation is the win rate of the best move, scaled like the neural network heuristic.
+pub fn run_search(
+    board: &Board,
+    limits: MctsLimits,
+    tree: &mut MctsTree,
+    mut evaluate: impl FnMut(&Board) -> LeafEvaluation,
+) -> SearchResult {
+    assert!(limits.playouts.is_some() || limits.time.is_some());
+    let t0 = Instant::now();
+    let is_only_move = board.possible_moves().len() == 1;
+    let mut nb_playouts = 0;
+    loop {
+        tree.run_iteration(&mut evaluate);
+        nb_playouts += 1;
+        // An unexpanded root (new tree, or reused leaf) is only expanded by the first iteration, which visits no move
+        if !tree.is_root_move_visited() {
+            continue;
+        }
+        if is_only_move
+            || limits
+                .playouts
+                .is_some_and(|max_playouts| nb_playouts >= max_playouts)
+            || limits.time.is_some_and(|duration| t0.elapsed() >= duration)
+        {
+            break;
+        }
+    }
+
+    let (moves, win_rate) = tree.principal_variation();
+    SearchResult {
+        best_move: moves[0].clone(),
+        principal_variations: vec![PrincipalVariation {
+            depth: moves.len() as i8,
+            score: ((win_rate - 0.5) * 1_000_000.) as i64,
+            moves,
+        }],
+        nodes: nb_playouts as u64,
+    }
+}
+
+/// Result in [0, 1], from the POV of the player who just moved
+fn random_playout(board: &Board, rng: &mut Rng) -> f64 {
+    let mut end_board = board.clone();
+    while !end_board.is_end_game() {
+        let mut possible_moves = end_board.possible_moves();
+        let i = rng.random_range(0..possible_moves.len());
+        end_board.play(&possible_moves.swap_remove(i));
+    }
+    let value = end_game_value(&end_board);
+    if end_board.get_player_is_white() == board.get_player_is_white() {
+        value
+    } else {
+        1. - value
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{MctsLimits, Playout, get_mcts_move};
+    use crate::checkers::board::Board;
+    use crate::checkers::pdn::{board_of_fen, move_of_pdn};
+    use crate::neural_network::neural_network::{NeuralNetworkTrait, generate_neural_networks};
+    use crate::players::alpha_beta::get_move::simple_heuristic;
+    use crate::players::mcts::tree::{LeafEvaluation, MctsTree, Selection};
+    use crate::players::neural_network::get_move::{input_of_board, neural_network_heuristic};
+    use crate::players::utils::heuristic::Heuristic;
+    use crate::random::seeded_rng;
+
+    const LIMITS: MctsLimits = MctsLimits {
+        playouts: Some(100),
+        time: None,
+    };
+
+    #[test]
+    fn single_move_on_new_tree() {
+        // Black has to capture 22
+        let board = board_of_fen("B:W22:B18").unwrap();
+        let mut tree = MctsTree::new(&board, Selection::Uct);
+        let search_result = get_mcts_move(
+            &board,
+            &simple_heuristic,
+            Playout::Random,
+            LIMITS,
+            &mut tree,
+            &mut seeded_rng(0),
+        );
+        assert_eq!(
+            search_result.best_move,
+            move_of_pdn(&board, "18x25").unwrap()
+        );
+    }
+
+    #[test]
+    fn single_move_on_reused_leaf() {
+        // After either white move, black has to capture
+        let previous_board = board_of_fen("W:W26:B18").unwrap();
+        let mut tree = MctsTree::new(&previous_board, Selection::Uct);
+        tree.run_iteration(&mut |_| LeafEvaluation {
+            value: 0.5,
+            priors: None,
+        });
+        let mut board = previous_board.clone();
+        board.play(&move_of_pdn(&previous_board, "26-22").unwrap());
+        let search_result = get_mcts_move(
+            &board,
+            &simple_heuristic,
+            Playout::Random,
+            LIMITS,
+            &mut tree,
+            &mut seeded_rng(0),
+        );
+        assert_eq!(
+            search_result.best_move,
+            move_of_pdn(&board, "18x25").unwrap()
+        );
+    }
+
+    #[test]
+    fn heuristic_playout_is_network_output() {
+        let neural_network = generate_neural_networks(&mut seeded_rng(0)).swap_remove(0);
+        let heuristic = neural_network_heuristic(&neural_network);
+        for board in [Board::new(), board_of_fen("W:W26:B18").unwrap()] {
+            let output = f64::from(neural_network.get_output(input_of_board(&board)));
+            let win_probability = heuristic.win_probability(heuristic.score(&board));
+            assert!((win_probability - output).abs() < 1e-5);
+        }
+    }
+}
@@ -0,0 +1,36 @@
+use crate::checkers::board::Board;
+use crate::consts::{MATCH_TIME_PER_MOVE, MCTS_PLAYOUT, SEARCH_PARAMETERS};
+use crate::players::alpha_beta::get_move::simple_heuristic;
+use crate::players::alpha_beta::search_match::{alpha_beta_player, run_match};
+use crate::players::mcts::get_move::{MctsLimits, get_mcts_move};
+use crate::players::mcts::tree::{MctsTree, Selection};
+use crate::random::{Rng, seeded_rng};
+
+// Plays MCTS against the alpha-beta search, both with the simple heuristic and `MATCH_TIME_PER_MOVE` per move.
+
+pub fn run_mcts_match(rng: &mut Rng) {
+    println!("MCTS ({:?} playouts) vs alpha-beta", MCTS_PLAYOUT);
+    run_match(
+        |seed| {
+            let mut tree = MctsTree::new(&Board::new(), Selection::Uct);
+            let mut rng = seeded_rng(seed);
+            move |board: &Board| {
+                let limits = MctsLimits {
+                    time: Some(MATCH_TIME_PER_MOVE),
+                    ..MctsLimits::default()
+                };
+                get_mcts_move(
+                    board,
+                    &simple_heuristic,
+                    MCTS_PLAYOUT,
+                    limits,
+                    &mut tree,
+                    &mut rng,
+                )
+                .best_move
+            }
+        },
+        |seed| alpha_beta_player(SEARCH_PARAMETERS, seed),
+        rng,
+    );
+}
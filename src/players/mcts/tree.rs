@@ -0,0 +1,263 @@
+use crate::checkers::board::{Board, Move};
+use crate::checkers::win_status::WinStatus::{Continue, Draw, Win};
+
+// Monte Carlo search tree, stored in an arena: node 0 is the root, and the other nodes are referenced by their index.
+// Each node holds the statistics of the move leading to it, from the POV of the player who played this move.
+
+/// UCT exploration constant (cf <https://en.wikipedia.org/wiki/Monte_Carlo_tree_search#Exploration_and_exploitation>)
+const EXPLORATION_CONSTANT: f64 = std::f64::consts::SQRT_2;
+/// PUCT exploration constant, which weights the prior probabilities
+const PUCT_CONSTANT: f64 = 1.5;
+/// With PUCT, value of the unvisited moves
+const PUCT_FIRST_PLAY_VALUE: f64 = 0.5;
+/// Subtrees are kept between two searches if the new root is reached in at most this number of plies
+const MAX_REUSE_PLIES: usize = 2;
+
+#[derive(Clone, Copy, Debug)]
+pub enum Selection {
+    /// Unvisited moves first, then UCT. The prior probabilities are ignored
+    Uct,
+    /// Predictor + UCT, guided by the prior probabilities (cf `get_puct_move`)
+    Puct,
+}
+
+/// Score of a leaf which is not an ended game
+pub struct LeafEvaluation {
+    /// In [0, 1], from the POV of the player who just moved
+    pub value: f64,
+    /// Prior probabilities of the possible moves, in `Board::possible_moves` order. Uniform if `None`
+    pub priors: Option<Vec<f64>>,
+}
+
+struct Node {
+    /// Move leading to this node (empty for the root)
+    m: Move,
+    prior: f64,
+    visits: u32,
+    /// Sum of the leaf values (cf `MctsTree::run_iteration`)
+    value_sum: f64,
+    /// Empty until the node is expanded
+    children: Vec<usize>,
+}
+
+impl Node {
+    fn new(m: Move, prior: f64) -> Self {
+        Node {
+            m,
+            prior,
+            visits: 0,
+            value_sum: 0.,
+            children: Vec::new(),
+        }
+    }
+
+    fn win_rate(&self) -> f64 {
+        self.value_sum / f64::from(u32::max(1, self.visits))
+    }
+}
+
+pub struct MctsTree {
+    selection: Selection,
+    root_board: Board,
+    nodes: Vec<Node>,
+}
+
+impl MctsTree {
+    pub fn new(board: &Board, selection: Selection) -> Self {
+        MctsTree {
+            selection,
+            root_board: board.clone(),
+            nodes: vec![Node::new(Vec::new(), 1.)],
+        }
+    }
+
+    /// Keeps the subtree of `board` if it was already explored (e.g. after a move of each player), otherwise starts a new tree
+    pub fn set_root(&mut self, board: &Board) {
+        let state_hash = board.state_hash();
+        let nodes = &self.nodes;
+        let mut layer = vec![(0, self.root_board.clone())];
+        for _ in 0..=MAX_REUSE_PLIES {
+            if let Some(&(node, _)) = layer.iter().find(|(_, b)| b.state_hash() == state_hash) {
+                self.nodes = self.subtree(node);
+                self.nodes[0].m = Vec::new();
+                self.root_board = board.clone();
+                return;
+            }
+            layer = layer
+                .into_iter()
+                .flat_map(|(node, b)| {
+                    nodes[node].children.iter().map(move |&child| {
+                        let mut child_board = b.clone();
+                        child_board.play(&nodes[child].m);
+                        (child, child_board)
+                    })
+                })
+                .collect();
+        }
+        *self = MctsTree::new(board, self.selection);
+    }
+
+    pub fn is_root_expanded(&self) -> bool {
+        !self.nodes[0].children.is_empty()
+    }
+
+    /// Number of iterations through the root, including those of the previous searches kept by `set_root`
+    #[cfg(test)]
+    pub fn root_visits(&self) -> u32 {
+        self.nodes[0].visits
+    }
+
+    /// Whether a root move has been visited, so that the principal variation is not empty
+    pub fn is_root_move_visited(&self) -> bool {
+        self.nodes[0]
+            .children
+            .iter()
+            .any(|&child| self.nodes[child].visits > 0)
+    }
+
+    /// Mixes the prior probabilities of the root moves with `noise` (cf `get_puct_move`)
+    pub fn add_root_noise(&mut self, noise: &[f64], noise_fraction: f64) {
+        let children = self.nodes[0].children.clone();
+        assert_eq!(children.len(), noise.len());
+        for (child, noise) in children.into_iter().zip(noise) {
+            let prior = &mut self.nodes[child].prior;
+            *prior = (1. - noise_fraction) * *prior + noise_fraction * noise;
+        }
+    }
+
+    /// Selection, then expansion and evaluation of the reached leaf (cf `LeafEvaluation`), then backpropagation
+    pub fn run_iteration(&mut self, evaluate: &mut impl FnMut(&Board) -> LeafEvaluation) {
+        let mut board = self.root_board.clone();
+        let mut path = vec![0];
+        let mut node = 0;
+        while !self.nodes[node].children.is_empty() {
+            node = self.select_child(node);
+            board.play(&self.nodes[node].m);
+            path.push(node);
+        }
+
+        let mut value = if board.is_end_game() {
+            end_game_value(&board)
+        } else {
+            let evaluation = evaluate(&board);
+            self.expand(node, &board, evaluation.priors);
+            evaluation.value
+        };
+        for node in path.into_iter().rev() {
+            self.nodes[node].visits += 1;
+            self.nodes[node].value_sum += value;
+            value = 1. - value;
+        }
+    }
+
+    /// Most visited moves from the root, and the win rate of the first one (root player POV)
+    pub fn principal_variation(&self) -> (Vec<Move>, f64) {
+        let mut moves = Vec::new();
+        let mut win_rate = 0.5;
+        let mut node = 0;
+        while let Some(&child) = self.nodes[node]
+            .children
+            .iter()
+            .max_by_key(|&&child| self.nodes[child].visits)
+            && self.nodes[child].visits > 0
+        {
+            if node == 0 {
+                win_rate = self.nodes[child].win_rate();
+            }
+            moves.push(self.nodes[child].m.clone());
+            node = child;
+        }
+        (moves, win_rate)
+    }
+
+    fn expand(&mut self, node: usize, board: &Board, priors: Option<Vec<f64>>) {
+        let possible_moves = board.possible_moves();
+        let nb_moves = possible_moves.len();
+        let priors = priors.unwrap_or_else(|| vec![1. / nb_moves as f64; nb_moves]);
+        assert_eq!(priors.len(), nb_moves);
+        for (m, prior) in possible_moves.into_iter().zip(priors) {
+            let child = self.nodes.len();
+            self.nodes[node].children.push(child);
+            self.nodes.push(Node::new(m, prior));
+        }
+    }
+
+    fn select_child(&self, node: usize) -> usize {
+        let visits = f64::from(self.nodes[node].visits);
+        let score = |child: usize| {
+            let child = &self.nodes[child];
+            match self.selection {
+                Selection::Uct => {
+                    if child.visits == 0 {
+                        return f64::INFINITY;
+                    }
+                    child.win_rate()
+                        + EXPLORATION_CONSTANT * (visits.ln() / f64::from(child.visits)).sqrt()
+                }
+                Selection::Puct => {
+                    let value = if child.visits == 0 {
+                        PUCT_FIRST_PLAY_VALUE
+                    } else {
+                        child.win_rate()
+                    };
+                    value
+                        + PUCT_CONSTANT * child.prior * visits.sqrt() / f64::from(1 + child.visits)
+                }
+            }
+        };
+        // On equality, the first child is chosen
+        *self.nodes[node]
+            .children
+            .iter()
+            .max_by(|&&child1, &&child2| {
+                score(child1)
+                    .total_cmp(&score(child2))
+                    .then(child2.cmp(&child1))
+            })
+            .unwrap()
+    }
+
+    /// Copy of the subtree of `node`, whose root is `node`
+    fn subtree(&self, node: usize) -> Vec<Node> {
+        let mut nodes = Vec::new();
+        let mut stack = vec![(node, None)];
+        while let Some((old_node, parent)) = stack.pop() {
+            let new_node = nodes.len();
+            let Node {
+                m,
+                prior,
+                visits,
+                value_sum,
+                children,
+            } = &self.nodes[old_node];
+            nodes.push(Node {
+                m: m.clone(),
+                prior: *prior,
+                visits: *visits,
+                value_sum: *value_sum,
+                children: Vec::new(),
+            });
+            if let Some(parent) = parent {
+                let parent: &mut Node = &mut nodes[parent];
+                parent.children.push(new_node);
+            }
+            stack.extend(children.iter().rev().map(|&child| (child, Some(new_node))));
+        }
+        nodes
+    }
+}
+
+/// Result of an ended game, in [0, 1], from the POV of the player who just moved
+pub fn end_game_value(board: &Board) -> f64 {
+    match board.get_win_status() {
+        Win(player) => {
+            if player.is_white() == board.get_player_is_white() {
+                0.
+            } else {
+                1.
+            }
+        }
+        Draw => 0.5,
+        Continue => panic!("Continue"),
+    }
+}
@@ -0,0 +1,160 @@
+use crate::checkers::board::Board;
+use crate::consts::NeuralNetworkFloat;
+use crate::neural_network::neural_network::NeuralNetworkParameters;
+use crate::neural_network::types::neural_network_base::float_sigmoid;
+use crate::players::neural_network::get_move::{
+    BOARD_COUNT_INPUT, MOVES_WITHOUT_CAPTURE_INPUT, draw_counters_inputs, piece_input, squares,
+};
+use ndarray::{Array1, Array2};
+
+// Efficiently updatable evaluation of the neural network (cf <https://www.chessprogramming.org/NNUE>), on the CPU.
+// At most 24 of the 128 piece inputs are set (cf `input_of_board`): instead of computing the whole first layer for each board,
+// the search keeps it in an accumulator (before the activation), which a move updates by subtracting the weight columns
+// of the pieces which left their square, and by adding the ones of the pieces which reached a square.
+// The piece inputs are from the current player POV: the accumulator is kept from the POV of both players.
+// The draw counters change at every move: their inputs are only added when evaluating.
+// The network is the trained one, whose parameters are exported (cf `NeuralNetworkTrait::export`).
+
+pub struct IncrementalNeuralNetwork {
+    /// Weights of the first layer, one column per input
+    first_layer_columns: Vec<Array1<NeuralNetworkFloat>>,
+    first_layer_biases: Array1<NeuralNetworkFloat>,
+    /// Weights and biases of the next layers
+    layers: Vec<(Array2<NeuralNetworkFloat>, Array1<NeuralNetworkFloat>)>,
+}
+
+/// First layer of the neural network for a board, without activation nor draw counters inputs
+#[derive(Clone)]
+pub struct Accumulator {
+    /// From the POV of White, then of Black
+    povs: [Array1<NeuralNetworkFloat>; 2],
+}
+
+impl IncrementalNeuralNetwork {
+    pub fn new(parameters: &NeuralNetworkParameters) -> Self {
+        let array2_of_layer = |l: usize| {
+            let weights = &parameters.weights[l];
+            Array2::from_shape_fn((weights.height(), weights.width()), |(i, j)| {
+                weights.get(i, j)
+            })
+        };
+        let array1_of_biases = |l: usize| {
+            let biases = &parameters.biases[l];
+            Array1::from_shape_fn(biases.height(), |i| biases.get(i, 0))
+        };
+        let first_layer = array2_of_layer(1);
+        IncrementalNeuralNetwork {
+            first_layer_columns: first_layer
+                .columns()
+                .into_iter()
+                .map(|column| column.to_owned())
+                .collect(),
+            first_layer_biases: array1_of_biases(1),
+            layers: (2..parameters.weights.len())
+                .map(|l| (array2_of_layer(l), array1_of_biases(l)))
+                .collect(),
+        }
+    }
+
+    pub fn accumulator(&self, board: &Board) -> Accumulator {
+        let povs = [true, false].map(|pov_is_white| {
+            let mut pov = self.first_layer_biases.clone();
+            for (square, x, y) in squares() {
+                if let Some(piece) = board.get(x, y) {
+                    pov += &self.first_layer_columns[piece_input(square, piece, pov_is_white)];
+                }
+            }
+            pov
+        });
+        Accumulator { povs }
+    }
+
+    /// Accumulator of `child_board`, reached by a move from `board`
+    pub fn play(
+        &self,
+        accumulator: &Accumulator,
+        board: &Board,
+        child_board: &Board,
+    ) -> Accumulator {
+        let mut accumulator = accumulator.clone();
+        for (square, x, y) in squares() {
+            let (piece, child_piece) = (board.get(x, y), child_board.get(x, y));
+            if piece == child_piece {
+                continue;
+            }
+            for (pov, pov_is_white) in accumulator.povs.iter_mut().zip([true, false]) {
+                if let Some(piece) = piece {
+                    *pov -= &self.first_layer_columns[piece_input(square, piece, pov_is_white)];
+                }
+                if let Some(child_piece) = child_piece {
+                    *pov +=
+                        &self.first_layer_columns[piece_input(square, child_piece, pov_is_white)];
+                }
+            }
+        }
+        accumulator
+    }
+
+    /// Same as `NeuralNetworkTrait::get_output`, `accumulator` being the one of `board`
+    pub fn get_output(&self, accumulator: &Accumulator, board: &Board) -> NeuralNetworkFloat {
+        let mut first_layer = accumulator.povs[usize::from(!board.get_player_is_white())].clone();
+        let [board_count_input, moves_without_capture_input] = draw_counters_inputs(board);
+        first_layer.scaled_add(
+            board_count_input,
+            &self.first_layer_columns[BOARD_COUNT_INPUT],
+        );
+        first_layer.scaled_add(
+            moves_without_capture_input,
+            &self.first_layer_columns[MOVES_WITHOUT_CAPTURE_INPUT],
+        );
+        let mut output = first_layer.mapv(float_sigmoid);
+        for (weights, biases) in &self.layers {
+            output = (weights.dot(&output) + biases).mapv(float_sigmoid);
+        }
+        output[0]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::IncrementalNeuralNetwork;
+    use crate::checkers::board::Board;
+    use crate::consts::NeuralNetwork;
+    use crate::neural_network::neural_network::{NeuralNetworkTrait, generate_neural_networks};
+    use crate::players::neural_network::get_move::input_of_board;
+    use crate::random::seeded_rng;
+
+    #[test]
+    fn accumulator_matches_full_evaluation() {
+        let neural_network: NeuralNetwork =
+            generate_neural_networks(&mut seeded_rng(0)).swap_remove(0);
+        let incremental_neural_network = IncrementalNeuralNetwork::new(&neural_network.export());
+        let mut board = Board::new();
+        let mut accumulator = incremental_neural_network.accumulator(&board);
+        // Captures, promotions and both players to move
+        for ply in 0..60 {
+            if board.is_end_game() {
+                break;
+            }
+            let full_accumulator = incremental_neural_network.accumulator(&board);
+            for (pov, full_pov) in accumulator.povs.iter().zip(&full_accumulator.povs) {
+                let max_error = (pov - full_pov).fold(0., |max, x| x.abs().max(max));
+                assert!(max_error < 1e-3, "ply {}: error {}", ply, max_error);
+            }
+            let output = incremental_neural_network.get_output(&accumulator, &board);
+            let expected = neural_network.get_output(input_of_board(&board));
+            assert!(
+                (output - expected).abs() < 1e-4,
+                "ply {}: {} instead of {}",
+                ply,
+                output,
+                expected
+            );
+            let moves = board.possible_moves();
+            let mut child_board = board.clone();
+            child_board.play(&moves[ply % moves.len()]);
+            accumulator = incremental_neural_network.play(&accumulator, &board, &child_board);
+            board = child_board;
+        }
+    }
+}
@@ -1,84 +1,375 @@
 use crate::checkers::board::{
     BOARD_SIZE, Board, MAX_BOARD_COUNT, MAX_MOVES_WITHOUT_CAPTURE, Move, is_playable,
 };
+use crate::checkers::piece::Piece;
 use crate::checkers::win_status::WinStatus::{Continue, Draw, Win};
-use crate::consts::{NeuralNetwork, NeuralNetworkFloat};
+use crate::consts::{
+    BOOK_CHOICE, NN_EVALUATION, NeuralNetwork, NeuralNetworkFloat, PLAY_BOOK_CHOICE,
+    SEARCH_PARAMETERS,
+};
 use crate::neural_network::neural_network::NeuralNetworkTrait;
 use crate::neural_network::types::matrix::Matrix;
 use crate::players::alpha_beta::get_move::{
     get_alpha_beta_move_depth_limit, get_alpha_beta_move_time_limit,
 };
-use ChooseMoveStrategy::{DepthLimit, TimeLimit, Training};
+use crate::players::alpha_beta::strength::{StrengthLevel, get_alpha_beta_move_with_level};
+use crate::players::mcts::get_move::{MctsLimits, Playout, get_mcts_move};
+use crate::players::mcts::tree::{MctsTree, Selection};
+use crate::players::neural_network::accumulator::{Accumulator, IncrementalNeuralNetwork};
+use crate::players::neural_network::puct::get_puct_move;
+use crate::players::neural_network::quantized::{QuantizedAccumulator, QuantizedNeuralNetwork};
+use crate::players::utils::alpha_beta::{SearchInfo, SearchResult};
+use crate::players::utils::heuristic::Heuristic;
+use crate::players::utils::opening_book::{BookChoice, book_search_result};
+use crate::players::utils::time_control::TimeBudget;
+use crate::players::utils::transposition_table::TranspositionTable;
+use crate::random::Rng;
+use ChooseMoveStrategy::{Clock, DepthLimit, Mcts, Puct, TimeLimit, Training};
+use rand::RngExt;
 use std::sync::Arc;
 use std::time::Duration;
 
+/// Inputs of the draw counters, after the 128 inputs of the pieces (cf `piece_input`)
+pub const BOARD_COUNT_INPUT: usize = 128;
+pub const MOVES_WITHOUT_CAPTURE_INPUT: usize = 129;
+
 #[derive(Clone, Copy)]
 pub enum ChooseMoveStrategy {
     DepthLimit(i8),
     TimeLimit(Duration),
+    /// Time allocated from a clock (cf `Clock::time_budget`), at a strength level (cf `StrengthLevel`)
+    Clock {
+        budget: TimeBudget,
+        level: StrengthLevel,
+        /// Fixed during a game (cf `get_alpha_beta_move_with_level`)
+        noise_salt: u64,
+    },
+    /// Monte Carlo tree search, the neural network scoring the leaves (cf `get_mcts_move`)
+    Mcts(MctsLimits),
+    /// PUCT tree search, the neural network giving the leaf values and the move priors (cf `get_puct_move`)
+    Puct {
+        limits: MctsLimits,
+        /// Dirichlet noise at the root, for self-play
+        root_noise: bool,
+    },
     Training,
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn get_neural_network_move(
     board: &Board,
     neural_network: &NeuralNetwork,
     move_strategy: ChooseMoveStrategy,
     threaded: bool,
-) -> Move {
-    let neural_network_clone = neural_network.clone();
-    let heuristic = Arc::new(move |board: &Board| {
-        // Current player POV
-        let f64_score = neural_network_clone.get_output(input_of_board(board));
-        ((f64_score - 0.5) * 1_000_000.) as i64
-    });
+    transposition_table: &Arc<TranspositionTable>,
+    heuristic: &mut Option<Arc<NeuralNetworkHeuristic>>,
+    tree: &mut Option<MctsTree>,
+    rng: &mut Rng,
+) -> SearchResult {
+    get_neural_network_move_with_info(
+        board,
+        neural_network,
+        move_strategy,
+        threaded,
+        transposition_table,
+        heuristic,
+        tree,
+        rng,
+        |_| (),
+    )
+}
+
+/// Except for `Training`, whose games must follow the network's own move probabilities, a book move
+/// (cf `BOOK_CHOICE`, and `PLAY_BOOK_CHOICE` on a clock) is played without search.
+/// `transposition_table`, `heuristic` (only built by the alpha-beta and MCTS strategies) and `tree` (only used by the
+/// tree search strategies) are kept between the moves of a game, with a single strategy, and are `None` before its first
+/// move: the network is only exported to its incremental evaluation once (cf `NeuralNetworkHeuristic::new`),
+/// and the subtree of the new board is reused (cf `MctsTree::set_root`).
+/// `on_search_info` is called after each iteration of time-limited searches (cf `get_alpha_beta_move_time_limit`)
+#[allow(clippy::too_many_arguments)]
+pub fn get_neural_network_move_with_info(
+    board: &Board,
+    neural_network: &NeuralNetwork,
+    move_strategy: ChooseMoveStrategy,
+    threaded: bool,
+    transposition_table: &Arc<TranspositionTable>,
+    heuristic: &mut Option<Arc<NeuralNetworkHeuristic>>,
+    tree: &mut Option<MctsTree>,
+    rng: &mut Rng,
+    on_search_info: impl FnMut(&SearchInfo),
+) -> SearchResult {
+    let book = match move_strategy {
+        Clock { .. } => PLAY_BOOK_CHOICE,
+        Training => BookChoice::Disabled,
+        _ => BOOK_CHOICE,
+    };
+    if let Some(search_result) = book_search_result(board, book, rng) {
+        return search_result;
+    }
+    let mut heuristic = || {
+        Arc::clone(
+            heuristic.get_or_insert_with(|| Arc::new(neural_network_heuristic(neural_network))),
+        )
+    };
     match move_strategy {
-        DepthLimit(depth_limit) => {
-            get_alpha_beta_move_depth_limit(board, heuristic, depth_limit, threaded)
-        }
-        TimeLimit(duration) => get_alpha_beta_move_time_limit(board, heuristic, duration, threaded),
+        DepthLimit(depth_limit) => get_alpha_beta_move_depth_limit(
+            board,
+            &heuristic(),
+            SEARCH_PARAMETERS,
+            depth_limit,
+            threaded,
+            transposition_table,
+            rng,
+        ),
+        TimeLimit(duration) => get_alpha_beta_move_time_limit(
+            board,
+            &heuristic(),
+            SEARCH_PARAMETERS,
+            TimeBudget::fixed(duration),
+            // Already probed
+            BookChoice::Disabled,
+            threaded,
+            transposition_table,
+            rng,
+            on_search_info,
+        ),
+        Clock {
+            budget,
+            level,
+            noise_salt,
+        } => get_alpha_beta_move_with_level(
+            board,
+            &heuristic(),
+            SEARCH_PARAMETERS,
+            level,
+            budget,
+            noise_salt,
+            threaded,
+            transposition_table,
+            rng,
+            on_search_info,
+        ),
+        Mcts(limits) => get_mcts_move(
+            board,
+            &*heuristic(),
+            Playout::Heuristic,
+            limits,
+            tree.get_or_insert_with(|| MctsTree::new(board, Selection::Uct)),
+            rng,
+        ),
+        Puct { limits, root_noise } => get_puct_move(
+            board,
+            neural_network,
+            limits,
+            root_noise,
+            tree.get_or_insert_with(|| MctsTree::new(board, Selection::Puct)),
+            rng,
+        ),
         Training => {
             assert!(!threaded);
-            get_neural_network_move_training(board, neural_network)
+            SearchResult {
+                best_move: get_neural_network_move_training(board, neural_network, rng),
+                principal_variations: Vec::new(),
+                nodes: 0,
+            }
         }
     }
 }
 
+/// How the neural network heuristic scores the boards of a search
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum NeuralNetworkEvaluation {
+    /// Each board on its own
+    Board,
+    /// The leaves of each node together (cf `Heuristic::is_batched`)
+    BatchedLeaves,
+    /// The first layer is updated at each move (cf `Accumulator`)
+    Accumulator,
+    /// Same as `Accumulator`, with the integer network exported from the float one (cf `QuantizedNeuralNetwork`)
+    Quantized,
+}
+
+/// Network whose first layer is updated at each move
+#[derive(Clone)]
+enum IncrementalEvaluation {
+    Float(Arc<IncrementalNeuralNetwork>),
+    Quantized(Arc<QuantizedNeuralNetwork>),
+}
+
+/// First layer of a board (cf `IncrementalEvaluation`)
+#[derive(Clone)]
+pub enum AccumulatorState {
+    Float(Accumulator),
+    Quantized(QuantizedAccumulator),
+}
+
+/// Heuristic of the search, scoring boards with a neural network (cf `neural_network_heuristic`)
+#[derive(Clone)]
+pub struct NeuralNetworkHeuristic {
+    neural_network: NeuralNetwork,
+    evaluation: NeuralNetworkEvaluation,
+    /// With `NeuralNetworkEvaluation::Accumulator` and `NeuralNetworkEvaluation::Quantized` only
+    incremental_evaluation: Option<IncrementalEvaluation>,
+}
+
+impl Heuristic for NeuralNetworkHeuristic {
+    type State = Option<AccumulatorState>;
+
+    fn score(&self, board: &Board) -> i64 {
+        score_of_output(self.neural_network.get_output(input_of_board(board)))
+    }
+
+    fn state(&self, board: &Board) -> Option<AccumulatorState> {
+        Some(match self.incremental_evaluation.as_ref()? {
+            IncrementalEvaluation::Float(neural_network) => {
+                AccumulatorState::Float(neural_network.accumulator(board))
+            }
+            IncrementalEvaluation::Quantized(neural_network) => {
+                AccumulatorState::Quantized(neural_network.accumulator(board))
+            }
+        })
+    }
+
+    fn play(
+        &self,
+        state: &Option<AccumulatorState>,
+        board: &Board,
+        child_board: &Board,
+    ) -> Option<AccumulatorState> {
+        Some(
+            match (self.incremental_evaluation.as_ref()?, state.as_ref()?) {
+                (
+                    IncrementalEvaluation::Float(neural_network),
+                    AccumulatorState::Float(accumulator),
+                ) => AccumulatorState::Float(neural_network.play(accumulator, board, child_board)),
+                (
+                    IncrementalEvaluation::Quantized(neural_network),
+                    AccumulatorState::Quantized(accumulator),
+                ) => AccumulatorState::Quantized(neural_network.play(
+                    accumulator,
+                    board,
+                    child_board,
+                )),
+                _ => unreachable!(),
+            },
+        )
+    }
+
+    fn score_with_state(&self, board: &Board, state: &Option<AccumulatorState>) -> i64 {
+        match (&self.incremental_evaluation, state) {
+            (
+                Some(IncrementalEvaluation::Float(neural_network)),
+                Some(AccumulatorState::Float(accumulator)),
+            ) => score_of_output(neural_network.get_output(accumulator, board)),
+            (
+                Some(IncrementalEvaluation::Quantized(neural_network)),
+                Some(AccumulatorState::Quantized(accumulator)),
+            ) => score_of_output(neural_network.get_output(accumulator, board)),
+            _ => self.score(board),
+        }
+    }
+
+    fn scores(&self, boards: &[Board]) -> Vec<i64> {
+        if boards.is_empty() {
+            return Vec::new();
+        }
+        let inputs = boards.iter().map(input_of_board).collect();
+        self.neural_network
+            .get_outputs(inputs)
+            .into_iter()
+            .map(score_of_output)
+            .collect()
+    }
+
+    fn score_scale(&self) -> f64 {
+        // The output is a win probability, whose slope around 0.5 is 1/4 per unit of log-odds (cf `score_of_output`)
+        1_000_000. / 4.
+    }
+
+    fn win_probability(&self, score: i64) -> f64 {
+        // The output itself (cf `score_of_output`)
+        (score as f64 / 1_000_000. + 0.5).clamp(0., 1.)
+    }
+
+    fn is_batched(&self) -> bool {
+        self.evaluation == NeuralNetworkEvaluation::BatchedLeaves
+    }
+}
+
+impl NeuralNetworkHeuristic {
+    pub fn new(
+        neural_network: &NeuralNetwork,
+        evaluation: NeuralNetworkEvaluation,
+    ) -> NeuralNetworkHeuristic {
+        let incremental_evaluation = match evaluation {
+            NeuralNetworkEvaluation::Board | NeuralNetworkEvaluation::BatchedLeaves => None,
+            NeuralNetworkEvaluation::Accumulator => Some(IncrementalEvaluation::Float(Arc::new(
+                IncrementalNeuralNetwork::new(&neural_network.export()),
+            ))),
+            NeuralNetworkEvaluation::Quantized => Some(IncrementalEvaluation::Quantized(Arc::new(
+                QuantizedNeuralNetwork::new(&neural_network.export()),
+            ))),
+        };
+        NeuralNetworkHeuristic {
+            neural_network: neural_network.clone(),
+            evaluation,
+            incremental_evaluation,
+        }
+    }
+}
+
+/// The boards are scored as set by `NN_EVALUATION`
+pub fn neural_network_heuristic(neural_network: &NeuralNetwork) -> NeuralNetworkHeuristic {
+    NeuralNetworkHeuristic::new(neural_network, NN_EVALUATION)
+}
+
+/// Current player POV
+fn score_of_output(output: NeuralNetworkFloat) -> i64 {
+    ((output - 0.5) * 1_000_000.) as i64
+}
+
 pub fn input_of_board(board: &Board) -> Matrix {
     let mut res = Matrix::zero(130, 1);
     let current_player = board.get_player_is_white();
-    let mut i = 0;
-    for x in 0..BOARD_SIZE {
-        for y in 0..BOARD_SIZE {
-            if is_playable(x, y) {
-                if let Some(p) = board.get(x, y) {
-                    let mut index = i;
-                    if p.is_white() == current_player {
-                        index += 2;
-                    }
-                    if p.is_king() {
-                        index += 1;
-                    }
-                    res.set(index, 0, 1.);
-                }
-                i += 4;
-            }
+    for (square, x, y) in squares() {
+        if let Some(p) = board.get(x, y) {
+            res.set(piece_input(square, p, current_player), 0, 1.);
         }
     }
-    res.set(
-        128,
-        0,
+    let [board_count_input, moves_without_capture_input] = draw_counters_inputs(board);
+    res.set(BOARD_COUNT_INPUT, 0, board_count_input);
+    res.set(MOVES_WITHOUT_CAPTURE_INPUT, 0, moves_without_capture_input);
+    res
+}
+
+/// Playable squares, numbered in the order of the inputs of the neural network, with their coordinates
+pub fn squares() -> impl Iterator<Item = (usize, i8, i8)> {
+    (0..BOARD_SIZE)
+        .flat_map(|x| (0..BOARD_SIZE).map(move |y| (x, y)))
+        .filter(|&(x, y)| is_playable(x, y))
+        .enumerate()
+        .map(|(square, (x, y))| (square, x, y))
+}
+
+/// Input set by `piece` on `square` (cf `squares`), from the POV of the player `pov_is_white`
+pub fn piece_input(square: usize, piece: Piece, pov_is_white: bool) -> usize {
+    4 * square + 2 * usize::from(piece.is_white() == pov_is_white) + usize::from(piece.is_king())
+}
+
+/// Values of the inputs `BOARD_COUNT_INPUT` and `MOVES_WITHOUT_CAPTURE_INPUT`
+pub fn draw_counters_inputs(board: &Board) -> [NeuralNetworkFloat; 2] {
+    [
         board.get_board_count() as NeuralNetworkFloat / MAX_BOARD_COUNT as NeuralNetworkFloat,
-    );
-    res.set(
-        129,
-        0,
         board.get_moves_without_capture() as NeuralNetworkFloat
             / MAX_MOVES_WITHOUT_CAPTURE as NeuralNetworkFloat,
-    );
-    res
+    ]
 }
 
-fn get_neural_network_move_training(board: &Board, neural_network: &NeuralNetwork) -> Move {
+fn get_neural_network_move_training(
+    board: &Board,
+    neural_network: &NeuralNetwork,
+    rng: &mut Rng,
+) -> Move {
     let mut moves = Vec::new();
     let mut inputs = Vec::new();
     let mut fixed_scores = Vec::new();
@@ -88,18 +379,7 @@ fn get_neural_network_move_training(board: &Board, neural_network: &NeuralNetwor
         board.play(&m);
         moves.push(m);
         inputs.push(input_of_board(&board));
-        fixed_scores.push(match board.get_win_status() {
-            Win(player) => {
-                // Not 1. or 0.: needs to be unsigmoid-safe
-                if player.is_white() == self_is_white {
-                    Some(0.999)
-                } else {
-                    Some(0.001)
-                }
-            }
-            Draw => Some(0.5),
-            Continue => None,
-        });
+        fixed_scores.push(end_game_score(&board, self_is_white));
     }
     let mut scores = neural_network
         .get_outputs(inputs)
@@ -112,10 +392,27 @@ fn get_neural_network_move_training(board: &Board, neural_network: &NeuralNetwor
         }
     }
 
-    choose_move_from_scores(scores, moves)
+    choose_move_from_scores(scores, moves, rng)
 }
 
-fn choose_move_from_scores(scores: Vec<NeuralNetworkFloat>, moves: Vec<Move>) -> Move {
+/// Score of `board` if the game is ended, from the POV of the player `self_is_white`
+pub fn end_game_score(board: &Board, self_is_white: bool) -> Option<NeuralNetworkFloat> {
+    match board.get_win_status() {
+        Win(player) => {
+            // Not 1. or 0.: needs to be unsigmoid-safe
+            if player.is_white() == self_is_white {
+                Some(0.999)
+            } else {
+                Some(0.001)
+            }
+        }
+        Draw => Some(0.5),
+        Continue => None,
+    }
+}
+
+/// Probability of playing each move, from the score of each move: softmax of the unsigmoided scores
+pub fn move_probabilities(scores: Vec<NeuralNetworkFloat>) -> Vec<NeuralNetworkFloat> {
     let mut scores = scores;
     for v in &mut scores {
         *v = NeuralNetworkFloat::exp(unsigmoid(*v));
@@ -126,7 +423,16 @@ fn choose_move_from_scores(scores: Vec<NeuralNetworkFloat>, moves: Vec<Move>) ->
     }
     let new_sum = scores.iter().sum::<NeuralNetworkFloat>();
     assert!((new_sum - 1.).abs() < 1e-5, "{}: {:?}", new_sum, scores);
-    let val = rand::random::<NeuralNetworkFloat>();
+    scores
+}
+
+fn choose_move_from_scores(
+    scores: Vec<NeuralNetworkFloat>,
+    moves: Vec<Move>,
+    rng: &mut Rng,
+) -> Move {
+    let scores = move_probabilities(scores);
+    let val = rng.random::<NeuralNetworkFloat>();
     let mut current_sum = 0.;
     for (move_id, m) in moves.into_iter().enumerate() {
         current_sum += scores[move_id];
@@ -0,0 +1,159 @@
+use crate::checkers::board::Board;
+use crate::consts::NeuralNetwork;
+use crate::neural_network::neural_network::NeuralNetworkTrait;
+use crate::players::mcts::get_move::{MctsLimits, run_search};
+use crate::players::mcts::tree::{LeafEvaluation, MctsTree};
+use crate::players::neural_network::get_move::{
+    end_game_score, input_of_board, move_probabilities,
+};
+use crate::players::utils::alpha_beta::SearchResult;
+use crate::players::utils::tablebase::{TablebaseResult, probe_tablebase};
+use crate::random::{Rng, random_dirichlet};
+
+// AlphaZero-style tree search (cf <https://arxiv.org/abs/1712.01815>).
+// The neural network has a single value output, so the move priors of a leaf are computed from the values of its children,
+// like the move probabilities of the training games (cf `move_probabilities`).
+// The leaf and its children are evaluated by a single batched call of the neural network.
+
+/// Dirichlet noise parameter, as for chess in the paper (the smaller, the more concentrated on a few moves)
+const DIRICHLET_ALPHA: f64 = 0.3;
+/// Weight of the Dirichlet noise in the root priors
+const ROOT_NOISE_FRACTION: f64 = 0.25;
+
+/// `tree` must use the PUCT selection, and is kept between the moves of a game (cf `get_mcts_move`).
+/// With `root_noise`, the root priors are mixed with Dirichlet noise, so that self-play games explore other moves.
+pub fn get_puct_move(
+    board: &Board,
+    neural_network: &NeuralNetwork,
+    limits: MctsLimits,
+    root_noise: bool,
+    tree: &mut MctsTree,
+    rng: &mut Rng,
+) -> SearchResult {
+    let mut evaluate = |board: &Board| evaluate_leaf(board, neural_network);
+    tree.set_root(board);
+    if root_noise {
+        if !tree.is_root_expanded() {
+            tree.run_iteration(&mut evaluate);
+        }
+        let noise = random_dirichlet(rng, DIRICHLET_ALPHA, board.possible_moves().len());
+        tree.add_root_noise(&noise, ROOT_NOISE_FRACTION);
+    }
+    run_search(board, limits, tree, evaluate)
+}
+
+fn evaluate_leaf(board: &Board, neural_network: &NeuralNetwork) -> LeafEvaluation {
+    let self_is_white = board.get_player_is_white();
+    let mut inputs = vec![input_of_board(board)];
+    let mut fixed_scores = Vec::new();
+    for m in board.possible_moves() {
+        let mut board = board.clone();
+        board.play(&m);
+        inputs.push(input_of_board(&board));
+        fixed_scores.push(end_game_score(&board, self_is_white));
+    }
+    let outputs = neural_network.get_outputs(inputs);
+
+    let scores = outputs[1..]
+        .iter()
+        .zip(fixed_scores)
+        .map(|(&opponent_score, score_opt)| score_opt.unwrap_or(1. - opponent_score))
+        .collect();
+    let priors = move_probabilities(scores)
+        .into_iter()
+        .map(f64::from)
+        .collect();
+    // Tablebase results and the neural network output are from the current player POV
+    let value = match probe_tablebase(board) {
+        Some(TablebaseResult::Win(_)) => 0.,
+        Some(TablebaseResult::Loss(_)) => 1.,
+        Some(TablebaseResult::Draw) => 0.5,
+        None => 1. - f64::from(outputs[0]),
+    };
+    LeafEvaluation {
+        value,
+        priors: Some(priors),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::get_puct_move;
+    use crate::checkers::pdn::{board_of_fen, move_of_pdn};
+    use crate::consts::NeuralNetwork;
+    use crate::neural_network::neural_network::generate_neural_networks;
+    use crate::players::mcts::get_move::MctsLimits;
+    use crate::players::mcts::tree::{MctsTree, Selection};
+    use crate::players::neural_network::get_move::{ChooseMoveStrategy, get_neural_network_move};
+    use crate::players::utils::transposition_table::TranspositionTable;
+    use crate::random::seeded_rng;
+    use std::sync::Arc;
+
+    const LIMITS: MctsLimits = MctsLimits {
+        playouts: Some(50),
+        time: None,
+    };
+
+    fn neural_network() -> NeuralNetwork {
+        generate_neural_networks(&mut seeded_rng(0)).swap_remove(0)
+    }
+
+    #[test]
+    fn single_move_without_noise() {
+        // Black has to capture 22
+        let board = board_of_fen("B:W22:B18").unwrap();
+        let mut tree = MctsTree::new(&board, Selection::Puct);
+        let search_result = get_puct_move(
+            &board,
+            &neural_network(),
+            LIMITS,
+            false,
+            &mut tree,
+            &mut seeded_rng(0),
+        );
+        assert_eq!(
+            search_result.best_move,
+            move_of_pdn(&board, "18x25").unwrap()
+        );
+    }
+
+    #[test]
+    fn tree_is_kept_between_moves() {
+        let neural_network = neural_network();
+        let transposition_table = Arc::new(TranspositionTable::new(1));
+        let move_strategy = ChooseMoveStrategy::Puct {
+            limits: LIMITS,
+            root_noise: false,
+        };
+        let mut board = board_of_fen("W:W7,12,13,20,25,26,29,30,31,32:B2,3,4,5,6,8,17").unwrap();
+        let (mut heuristic, mut tree) = (None, None);
+        let mut rng = seeded_rng(0);
+        let search_result = get_neural_network_move(
+            &board,
+            &neural_network,
+            move_strategy,
+            false,
+            &transposition_table,
+            &mut heuristic,
+            &mut tree,
+            &mut rng,
+        );
+        // The move of each player, as expected by the search
+        let moves = &search_result.principal_variations[0].moves;
+        assert!(moves.len() >= 2);
+        board.play(&moves[0]);
+        board.play(&moves[1]);
+        let search_result = get_neural_network_move(
+            &board,
+            &neural_network,
+            move_strategy,
+            false,
+            &transposition_table,
+            &mut heuristic,
+            &mut tree,
+            &mut rng,
+        );
+        // The root keeps the visits of the previous search
+        assert!(u64::from(tree.unwrap().root_visits()) > search_result.nodes);
+    }
+}
@@ -0,0 +1,490 @@
+use crate::checkers::board::{BOARD_SIZE, Board, NB_PLAYERS_LINES};
+use crate::consts::{NeuralNetwork, NeuralNetworkFloat};
+use crate::neural_network::neural_network::{NeuralNetworkParameters, NeuralNetworkTrait};
+use crate::neural_network::types::matrix::Matrix;
+use crate::neural_network::types::neural_network_base::float_sigmoid;
+use crate::players::neural_network::get_move::{
+    BOARD_COUNT_INPUT, MOVES_WITHOUT_CAPTURE_INPUT, draw_counters_inputs, input_of_board,
+    piece_input, squares,
+};
+use crate::random::Rng;
+use rand::RngExt;
+use std::cmp::Ordering;
+use std::sync::OnceLock;
+
+// Quantized evaluation of the neural network (cf <https://www.chessprogramming.org/NNUE>), with integers only, on the CPU.
+// The first layer has int16 weights, and is kept in an accumulator updated at each move (cf `accumulator.rs`).
+// The next layers have int8 weights, and their inputs are int8 activations, summed in int32.
+// The sigmoid of the hidden layers is read from a lookup table of int8 activations, indexed by a multiplication and
+// a shift: the quantized network approximates the trained one, with the same activations.
+// The weights of each layer are scaled to use the whole range of their integers (cf `QuantizedNeuralNetwork::new`),
+// and the rows are padded to a multiple of SIMD_WIDTH, so that the compiler vectorizes the dot products.
+
+/// Number of int8 lanes of a 256-bit SIMD register
+const SIMD_WIDTH: usize = 32;
+/// Activations are in `0..=ACTIVATION_ONE`, which stands for 1
+const ACTIVATION_ONE: i32 = 127;
+/// Fixed-point precision of the activation multipliers
+const ACTIVATION_SHIFT: u32 = 32;
+/// The sigmoid rounds to 0 or `ACTIVATION_ONE` beyond this float value
+const SIGMOID_RANGE: NeuralNetworkFloat = 8.;
+/// Entries of the sigmoid table per float unit: the sigmoid varies by at most 1/4 of a step within an entry
+const SIGMOID_STEPS: usize = 64;
+/// Maximum number of pieces on a board, which bounds the accumulator
+const MAX_PIECES: usize = (NB_PLAYERS_LINES * BOARD_SIZE) as usize;
+/// Number of random games whose boards are the positions of the quantization report
+const REPORT_GAMES: usize = 200;
+/// Mean output error against the float network below which the quantized network may be the search evaluator
+/// (cf `NN_EVALUATION`)
+pub const MAX_MEAN_OUTPUT_ERROR: NeuralNetworkFloat = 0.01;
+
+static SIGMOID_TABLE: OnceLock<Vec<u8>> = OnceLock::new();
+
+/// Sigmoid of int32 values, which are the float ones times `scale`
+#[derive(Clone, Copy)]
+struct Activation {
+    /// Values saturate beyond `bound`, i.e. `SIGMOID_RANGE` in float
+    bound: i32,
+    /// Index of the sigmoid table per value, in fixed point
+    multiplier: i64,
+    table: &'static [u8],
+}
+
+impl Activation {
+    fn new(scale: NeuralNetworkFloat) -> Activation {
+        Activation {
+            bound: (SIGMOID_RANGE * scale).round() as i32,
+            multiplier: (SIGMOID_STEPS as f64 * (1u64 << ACTIVATION_SHIFT) as f64
+                / f64::from(scale))
+            .round() as i64,
+            table: SIGMOID_TABLE.get_or_init(sigmoid_table),
+        }
+    }
+
+    /// Approximation of the sigmoid, times `ACTIVATION_ONE`
+    fn apply(self, value: i32) -> u8 {
+        let value = i64::from(value.clamp(-self.bound, self.bound) + self.bound);
+        let index = (value * self.multiplier) >> ACTIVATION_SHIFT;
+        self.table[usize::min(index as usize, self.table.len() - 1)]
+    }
+}
+
+/// Sigmoid times `ACTIVATION_ONE`, in the middle of each step of `-SIGMOID_RANGE..SIGMOID_RANGE`
+fn sigmoid_table() -> Vec<u8> {
+    let nb_steps = 2 * SIGMOID_RANGE as usize * SIGMOID_STEPS;
+    (0..nb_steps)
+        .map(|step| {
+            let x = (step as NeuralNetworkFloat + 0.5) / SIGMOID_STEPS as NeuralNetworkFloat
+                - SIGMOID_RANGE;
+            (float_sigmoid(x) * ACTIVATION_ONE as NeuralNetworkFloat).round() as u8
+        })
+        .collect()
+}
+
+/// Layer after the first one
+struct QuantizedLayer {
+    /// Row-major, each row being padded to `input_len`
+    weights: Vec<i8>,
+    input_len: usize,
+    /// Times `scale * ACTIVATION_ONE`
+    biases: Vec<i32>,
+    /// The weights are the float ones times `scale`
+    scale: NeuralNetworkFloat,
+    activation: Activation,
+}
+
+impl QuantizedLayer {
+    fn new(weights: &Matrix, biases: &Matrix) -> QuantizedLayer {
+        let input_len = padded(weights.width());
+        let max_weight = max_abs(
+            (0..weights.height())
+                .flat_map(|i| (0..weights.width()).map(move |j| weights.get(i, j))),
+        );
+        let scale = NeuralNetworkFloat::from(i8::MAX) / max_weight;
+        let mut quantized_weights = vec![0; weights.height() * input_len];
+        for i in 0..weights.height() {
+            for j in 0..weights.width() {
+                quantized_weights[i * input_len + j] = (weights.get(i, j) * scale).round() as i8;
+            }
+        }
+        let output_scale = scale * ACTIVATION_ONE as NeuralNetworkFloat;
+        QuantizedLayer {
+            weights: quantized_weights,
+            input_len,
+            biases: (0..biases.height())
+                .map(|i| (biases.get(i, 0) * output_scale).round() as i32)
+                .collect(),
+            scale: output_scale,
+            activation: Activation::new(output_scale),
+        }
+    }
+
+    /// Before activation, times `scale`
+    fn outputs<'a>(&'a self, input: &'a [u8]) -> impl Iterator<Item = i32> + 'a {
+        self.weights
+            .chunks_exact(self.input_len)
+            .zip(&self.biases)
+            .map(move |(row, bias)| bias + dot(row, input))
+    }
+}
+
+pub struct QuantizedNeuralNetwork {
+    /// Number of neurons of the first layer, padded
+    first_layer_len: usize,
+    /// One column per input, each one padded to `first_layer_len`
+    first_layer_columns: Vec<i16>,
+    first_layer_biases: Vec<i16>,
+    /// Of the accumulator, with the draw counters inputs
+    first_layer_activation: Activation,
+    layers: Vec<QuantizedLayer>,
+}
+
+/// First layer of the quantized neural network for a board, without activation nor draw counters inputs
+#[derive(Clone)]
+pub struct QuantizedAccumulator {
+    /// From the POV of White, then of Black
+    povs: [Vec<i16>; 2],
+}
+
+impl QuantizedNeuralNetwork {
+    /// The first layer is scaled so that the accumulator of any board fits in int16,
+    /// and each next layer so that its largest weight is `i8::MAX`
+    pub fn new(parameters: &NeuralNetworkParameters) -> Self {
+        assert!(parameters.weights.len() > 2, "No hidden layer");
+        let (weights, biases) = (&parameters.weights[1], &parameters.biases[1]);
+        let first_layer_len = padded(weights.height());
+        let max_accumulator = max_abs((0..weights.height()).map(|i| {
+            let max_piece_weight = max_abs((0..BOARD_COUNT_INPUT).map(|j| weights.get(i, j)));
+            biases.get(i, 0).abs() + MAX_PIECES as NeuralNetworkFloat * max_piece_weight
+        }));
+        let max_draw_counters_weight = max_abs((0..weights.height()).flat_map(|i| {
+            [BOARD_COUNT_INPUT, MOVES_WITHOUT_CAPTURE_INPUT].map(|j| weights.get(i, j))
+        }));
+        // The weights of the first layer are the float ones times `first_layer_scale`
+        let first_layer_scale =
+            NeuralNetworkFloat::from(i16::MAX) / max_accumulator.max(max_draw_counters_weight);
+        let mut first_layer_columns = vec![0; weights.width() * first_layer_len];
+        for j in 0..weights.width() {
+            for i in 0..weights.height() {
+                first_layer_columns[j * first_layer_len + i] =
+                    (weights.get(i, j) * first_layer_scale).round() as i16;
+            }
+        }
+        let mut first_layer_biases = vec![0; first_layer_len];
+        for (i, bias) in first_layer_biases
+            .iter_mut()
+            .enumerate()
+            .take(biases.height())
+        {
+            *bias = (biases.get(i, 0) * first_layer_scale).round() as i16;
+        }
+        QuantizedNeuralNetwork {
+            first_layer_len,
+            first_layer_columns,
+            first_layer_biases,
+            first_layer_activation: Activation::new(
+                first_layer_scale * ACTIVATION_ONE as NeuralNetworkFloat,
+            ),
+            layers: (2..parameters.weights.len())
+                .map(|l| QuantizedLayer::new(&parameters.weights[l], &parameters.biases[l]))
+                .collect(),
+        }
+    }
+
+    pub fn accumulator(&self, board: &Board) -> QuantizedAccumulator {
+        let povs = [true, false].map(|pov_is_white| {
+            let mut pov = self.first_layer_biases.clone();
+            for (square, x, y) in squares() {
+                if let Some(piece) = board.get(x, y) {
+                    add_column(
+                        &mut pov,
+                        self.column(piece_input(square, piece, pov_is_white)),
+                    );
+                }
+            }
+            pov
+        });
+        QuantizedAccumulator { povs }
+    }
+
+    /// Accumulator of `child_board`, reached by a move from `board`
+    pub fn play(
+        &self,
+        accumulator: &QuantizedAccumulator,
+        board: &Board,
+        child_board: &Board,
+    ) -> QuantizedAccumulator {
+        let mut accumulator = accumulator.clone();
+        for (square, x, y) in squares() {
+            let (piece, child_piece) = (board.get(x, y), child_board.get(x, y));
+            if piece == child_piece {
+                continue;
+            }
+            for (pov, pov_is_white) in accumulator.povs.iter_mut().zip([true, false]) {
+                if let Some(piece) = piece {
+                    sub_column(pov, self.column(piece_input(square, piece, pov_is_white)));
+                }
+                if let Some(child_piece) = child_piece {
+                    add_column(
+                        pov,
+                        self.column(piece_input(square, child_piece, pov_is_white)),
+                    );
+                }
+            }
+        }
+        accumulator
+    }
+
+    /// Approximation of `NeuralNetworkTrait::get_output`, `accumulator` being the one of `board`
+    pub fn get_output(
+        &self,
+        accumulator: &QuantizedAccumulator,
+        board: &Board,
+    ) -> NeuralNetworkFloat {
+        let pov = &accumulator.povs[usize::from(!board.get_player_is_white())];
+        let [board_count_input, moves_without_capture_input] = draw_counters_inputs(board)
+            .map(|input| (input * ACTIVATION_ONE as NeuralNetworkFloat).round() as i32);
+        let mut input = pov
+            .iter()
+            .zip(self.column(BOARD_COUNT_INPUT))
+            .zip(self.column(MOVES_WITHOUT_CAPTURE_INPUT))
+            .map(
+                |((&value, &board_count_weight), &moves_without_capture_weight)| {
+                    self.first_layer_activation.apply(
+                        (i32::from(value) * ACTIVATION_ONE)
+                            .wrapping_add(i32::from(board_count_weight) * board_count_input)
+                            .wrapping_add(
+                                i32::from(moves_without_capture_weight)
+                                    * moves_without_capture_input,
+                            ),
+                    )
+                },
+            )
+            .collect::<Vec<_>>();
+        let (output_layer, hidden_layers) = self.layers.split_last().unwrap();
+        for (layer, next_layer) in hidden_layers.iter().zip(&self.layers[1..]) {
+            let mut output = layer
+                .outputs(&input)
+                .map(|value| layer.activation.apply(value))
+                .collect::<Vec<_>>();
+            output.resize(next_layer.input_len, 0);
+            input = output;
+        }
+        let output = output_layer.outputs(&input).next().unwrap();
+        float_sigmoid(output as NeuralNetworkFloat / output_layer.scale)
+    }
+
+    fn column(&self, input: usize) -> &[i16] {
+        &self.first_layer_columns[input * self.first_layer_len..(input + 1) * self.first_layer_len]
+    }
+}
+
+/// Compares the outputs of the quantized network with the float ones, on the boards of `REPORT_GAMES` random games
+pub fn run_quantization_report(neural_network: &NeuralNetwork, rng: &mut Rng) {
+    let parameters = neural_network.export();
+    let quantized_neural_network = QuantizedNeuralNetwork::new(&parameters);
+    let outputs = |board: &Board| {
+        (
+            neural_network.get_output(input_of_board(board)),
+            quantized_neural_network
+                .get_output(&quantized_neural_network.accumulator(board), board),
+        )
+    };
+    let mut errors = Vec::new();
+    let (mut nb_choices, mut nb_same_moves) = (0, 0);
+    for board in random_games_boards(REPORT_GAMES, rng) {
+        let (output, quantized_output) = outputs(&board);
+        errors.push((quantized_output - output).abs());
+        let children_outputs = board
+            .possible_moves()
+            .iter()
+            .map(|m| {
+                let mut child_board = board.clone();
+                child_board.play(m);
+                outputs(&child_board)
+            })
+            .collect::<Vec<_>>();
+        if children_outputs.len() > 1 {
+            // The best move minimizes the score of the opponent
+            let best_move =
+                |output: fn(&(NeuralNetworkFloat, NeuralNetworkFloat)) -> NeuralNetworkFloat| {
+                    (0..children_outputs.len())
+                        .min_by(|&i, &j| {
+                            output(&children_outputs[i])
+                                .partial_cmp(&output(&children_outputs[j]))
+                                .unwrap_or(Ordering::Equal)
+                        })
+                        .unwrap()
+                };
+            nb_choices += 1;
+            nb_same_moves +=
+                usize::from(best_move(|outputs| outputs.0) == best_move(|outputs| outputs.1));
+        }
+    }
+    // One man is worth 1_000_000, i.e. an output of 1 (cf `score_of_output`)
+    println!("{} positions, output errors in men", errors.len());
+    let mean_error = mean(&errors);
+    print_errors("Quantized vs float network", &mut errors);
+    println!(
+        "Same best move (by the scores of the next boards): {}/{} positions",
+        nb_same_moves, nb_choices
+    );
+    println!(
+        "Mean error {:.4} {} {}: the quantized network {} replace the float one as the search evaluator",
+        mean_error,
+        if mean_error <= MAX_MEAN_OUTPUT_ERROR {
+            "<="
+        } else {
+            ">"
+        },
+        MAX_MEAN_OUTPUT_ERROR,
+        if mean_error <= MAX_MEAN_OUTPUT_ERROR {
+            "can"
+        } else {
+            "cannot"
+        }
+    );
+}
+
+fn mean(values: &[NeuralNetworkFloat]) -> NeuralNetworkFloat {
+    values.iter().sum::<NeuralNetworkFloat>() / values.len() as NeuralNetworkFloat
+}
+
+fn print_errors(name: &str, errors: &mut [NeuralNetworkFloat]) {
+    errors.sort_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+    println!(
+        "{} - mean: {:.4}, median: {:.4}, 99th percentile: {:.4}, max: {:.4}",
+        name,
+        mean(errors),
+        errors[errors.len() / 2],
+        errors[errors.len() * 99 / 100],
+        errors[errors.len() - 1]
+    );
+}
+
+/// Boards of `nb_games` games of random moves
+fn random_games_boards(nb_games: usize, rng: &mut Rng) -> Vec<Board> {
+    let mut boards = Vec::new();
+    for _ in 0..nb_games {
+        let mut board = Board::new();
+        while !board.is_end_game() {
+            boards.push(board.clone());
+            let mut possible_moves = board.possible_moves();
+            board.play(&possible_moves.swap_remove(rng.random_range(0..possible_moves.len())));
+        }
+    }
+    boards
+}
+
+/// Multiple of `SIMD_WIDTH`
+fn padded(len: usize) -> usize {
+    len.div_ceil(SIMD_WIDTH) * SIMD_WIDTH
+}
+
+/// 1 if `values` are all 0, so that scaling by its inverse is harmless
+fn max_abs(values: impl Iterator<Item = NeuralNetworkFloat>) -> NeuralNetworkFloat {
+    let max = values.fold(0., |max, value| value.abs().max(max));
+    if max == 0. { 1. } else { max }
+}
+
+fn dot(row: &[i8], input: &[u8]) -> i32 {
+    #[cfg(target_arch = "x86_64")]
+    if is_x86_feature_detected!("avx2") {
+        // SAFETY: AVX2 is available
+        return unsafe { dot_avx2(row, input) };
+    }
+    dot_generic(row, input)
+}
+
+/// Same as `dot_generic`, vectorized with AVX2 instructions
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx2")]
+fn dot_avx2(row: &[i8], input: &[u8]) -> i32 {
+    dot_generic(row, input)
+}
+
+/// Wrapping, so that it is vectorized despite `overflow-checks`: the rows are short enough not to overflow
+#[inline]
+fn dot_generic(row: &[i8], input: &[u8]) -> i32 {
+    row.iter().zip(input).fold(0, |sum, (&weight, &value)| {
+        sum.wrapping_add(i32::from(weight) * i32::from(value))
+    })
+}
+
+/// Wrapping, since only the accumulators of boards are bounded (cf `QuantizedNeuralNetwork::new`)
+fn add_column(pov: &mut [i16], column: &[i16]) {
+    for (value, weight) in pov.iter_mut().zip(column) {
+        *value = value.wrapping_add(*weight);
+    }
+}
+
+fn sub_column(pov: &mut [i16], column: &[i16]) {
+    for (value, weight) in pov.iter_mut().zip(column) {
+        *value = value.wrapping_sub(*weight);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{MAX_MEAN_OUTPUT_ERROR, QuantizedNeuralNetwork, mean, random_games_boards};
+    use crate::checkers::board::Board;
+    use crate::neural_network::neural_network::{NeuralNetworkTrait, generate_neural_network};
+    use crate::players::neural_network::get_move::input_of_board;
+    use crate::random::seeded_rng;
+
+    #[test]
+    fn quantized_network_stays_close_to_float_network() {
+        let boards = random_games_boards(2, &mut seeded_rng(0));
+        for seed in 0..3 {
+            let neural_network = generate_neural_network(&mut seeded_rng(seed));
+            let quantized_neural_network = QuantizedNeuralNetwork::new(&neural_network.export());
+            let errors = boards
+                .iter()
+                .map(|board| {
+                    let output = quantized_neural_network
+                        .get_output(&quantized_neural_network.accumulator(board), board);
+                    (output - neural_network.get_output(input_of_board(board))).abs()
+                })
+                .collect::<Vec<_>>();
+            assert!(
+                mean(&errors) < MAX_MEAN_OUTPUT_ERROR,
+                "seed {}: {}",
+                seed,
+                mean(&errors)
+            );
+        }
+    }
+
+    #[test]
+    fn updated_accumulator_is_exact() {
+        let neural_network = generate_neural_network(&mut seeded_rng(0));
+        let quantized_neural_network = QuantizedNeuralNetwork::new(&neural_network.export());
+        let mut board = Board::new();
+        let mut accumulator = quantized_neural_network.accumulator(&board);
+        for ply in 0..60 {
+            if board.is_end_game() {
+                break;
+            }
+            assert_eq!(
+                accumulator.povs,
+                quantized_neural_network.accumulator(&board).povs,
+                "ply {}",
+                ply
+            );
+            let output = quantized_neural_network.get_output(&accumulator, &board);
+            let expected = neural_network.get_output(input_of_board(&board));
+            assert!(
+                (output - expected).abs() < 0.05,
+                "ply {}: {} instead of {}",
+                ply,
+                output,
+                expected
+            );
+            let moves = board.possible_moves();
+            let mut child_board = board.clone();
+            child_board.play(&moves[ply % moves.len()]);
+            accumulator = quantized_neural_network.play(&accumulator, &board, &child_board);
+            board = child_board;
+        }
+    }
+}
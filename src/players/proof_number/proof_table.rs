@@ -0,0 +1,65 @@
+// Memory-bounded table of the proof and disproof numbers (cf `ProofNumberSearch`), indexed by `Board::exact_state_hash`.
+// When a bucket is full, the entry whose subtree needed the least work is replaced: it is the cheapest one to search again.
+
+const ENTRIES_PER_BUCKET: usize = 4;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ProofEntry {
+    pub proof: u64,
+    pub disproof: u64,
+    /// Number of nodes searched to get these numbers
+    pub work: u64,
+}
+
+pub struct ProofTable {
+    entries: Vec<Option<(u64, ProofEntry)>>,
+    nb_buckets: usize,
+}
+
+impl ProofTable {
+    pub fn new(size_in_mib: usize) -> Self {
+        let bucket_size = ENTRIES_PER_BUCKET * size_of::<Option<(u64, ProofEntry)>>();
+        let max_nb_buckets = usize::max(1, (size_in_mib << 20) / bucket_size);
+        // Largest power of two, so that `key % nb_buckets` is cheap
+        let nb_buckets = 1 << max_nb_buckets.ilog2();
+        ProofTable {
+            entries: vec![None; nb_buckets * ENTRIES_PER_BUCKET],
+            nb_buckets,
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.fill(None);
+    }
+
+    pub fn get(&self, key: u64) -> Option<ProofEntry> {
+        self.bucket(key)
+            .iter()
+            .find_map(|slot| slot.filter(|&(slot_key, _)| slot_key == key))
+            .map(|(_, entry)| entry)
+    }
+
+    pub fn insert(&mut self, key: u64, entry: ProofEntry) {
+        let bucket = self.bucket_mut(key);
+        let slot = bucket
+            .iter()
+            .position(|slot| slot.is_some_and(|(slot_key, _)| slot_key == key))
+            .or_else(|| bucket.iter().position(Option::is_none))
+            .unwrap_or_else(|| {
+                (0..ENTRIES_PER_BUCKET)
+                    .min_by_key(|&slot| bucket[slot].unwrap().1.work)
+                    .unwrap()
+            });
+        bucket[slot] = Some((key, entry));
+    }
+
+    fn bucket(&self, key: u64) -> &[Option<(u64, ProofEntry)>] {
+        let index = (key as usize % self.nb_buckets) * ENTRIES_PER_BUCKET;
+        &self.entries[index..index + ENTRIES_PER_BUCKET]
+    }
+
+    fn bucket_mut(&mut self, key: u64) -> &mut [Option<(u64, ProofEntry)>] {
+        let index = (key as usize % self.nb_buckets) * ENTRIES_PER_BUCKET;
+        &mut self.entries[index..index + ENTRIES_PER_BUCKET]
+    }
+}
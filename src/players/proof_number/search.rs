@@ -0,0 +1,378 @@
+use crate::checkers::board::{Board, Move};
+use crate::checkers::pdn::{MoveTree, pdn_of_move};
+use crate::checkers::win_status::WinStatus::{Continue, Draw, Win};
+use crate::players::proof_number::proof_table::{ProofEntry, ProofTable};
+use crate::players::utils::tablebase::{TablebaseResult, probe_tablebase};
+use std::fmt::{Display, Formatter};
+
+// Depth-first proof-number search (df-pn, cf <https://en.wikipedia.org/wiki/Proof-number_search>).
+// The attacker (the player to move at the root) tries to reach a goal, the defender tries to prevent it.
+// The proof (resp. disproof) number of a node is the minimum number of leaves to prove (resp. disprove) to prove (resp. disprove) it:
+// it is 0 once the node is proven (resp. disproven), and infinite once it is disproven (resp. proven).
+// Each node is searched until its numbers reach thresholds given by its parent, the numbers being kept in a `ProofTable`.
+
+pub const PROOF_INFINITY: u64 = u64::MAX;
+/// `on_progress` is called every `NODES_BETWEEN_PROGRESS` nodes
+const NODES_BETWEEN_PROGRESS: u64 = 1 << 20;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Goal {
+    Win,
+    AtLeastDraw,
+}
+
+impl Display for Goal {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Goal::Win => write!(f, "a win"),
+            Goal::AtLeastDraw => write!(f, "at least a draw"),
+        }
+    }
+}
+
+pub struct ProofProgress<'a> {
+    pub nodes: u64,
+    /// Proof and disproof numbers of the root
+    pub root: (u64, u64),
+    /// Moves from the root to the node being searched
+    pub line: &'a [Move],
+}
+
+impl Display for ProofProgress<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} nodes, proof number {}, disproof number {}, line {}",
+            self.nodes,
+            self.root.0,
+            self.root.1,
+            self.line
+                .iter()
+                .map(pdn_of_move)
+                .collect::<Vec<_>>()
+                .join(" ")
+        )
+    }
+}
+
+pub struct ProofNumberSearch<'a, F: FnMut(&ProofProgress)> {
+    root: Board,
+    goal: Goal,
+    /// If `true`, the positions of the tablebase are leaves (cf `probe_tablebase`)
+    use_tablebase: bool,
+    table: &'a mut ProofTable,
+    nodes: u64,
+    line: Vec<Move>,
+    on_progress: F,
+}
+
+impl<'a, F: FnMut(&ProofProgress)> ProofNumberSearch<'a, F> {
+    pub fn new(
+        root: &Board,
+        goal: Goal,
+        use_tablebase: bool,
+        table: &'a mut ProofTable,
+        on_progress: F,
+    ) -> Self {
+        ProofNumberSearch {
+            root: root.clone(),
+            goal,
+            use_tablebase,
+            table,
+            nodes: 0,
+            line: Vec::new(),
+            on_progress,
+        }
+    }
+
+    pub fn get_nodes(&self) -> u64 {
+        self.nodes
+    }
+
+    /// Searches until the root is proven (`true`) or disproven (`false`)
+    pub fn prove(&mut self) -> bool {
+        let root = self.root.clone();
+        let (proof, _) = self.search(&root, (PROOF_INFINITY, PROOF_INFINITY));
+        proof == 0
+    }
+
+    /// Once the root is solved, tree of the moves proving (`proven`) or disproving the goal:
+    /// one move of the player reaching their goal, and every reply of the other player.
+    /// The nodes evicted from the table are searched again
+    pub fn solution_tree(&mut self, proven: bool) -> MoveTree {
+        let root = self.root.clone();
+        self.solution_subtree(&root, proven)
+    }
+
+    fn search(&mut self, board: &Board, thresholds: (u64, u64)) -> (u64, u64) {
+        self.add_node();
+        let key = board.exact_state_hash();
+        let first_node = self.nodes;
+        if let Some(numbers) = self.leaf_numbers(board) {
+            self.store(key, numbers, 1);
+            return numbers;
+        }
+
+        let is_attacker = self.is_attacker(board);
+        let children = children(board);
+        loop {
+            let numbers = children
+                .iter()
+                .map(|&(_, _, child_key)| self.numbers(child_key))
+                .collect::<Vec<_>>();
+            let (proof, disproof) = combine(is_attacker, &numbers);
+            self.store(key, (proof, disproof), self.nodes - first_node + 1);
+            if proof >= thresholds.0 || disproof >= thresholds.1 {
+                return (proof, disproof);
+            }
+
+            // The most proving child: the easiest one to prove for the attacker, to disprove for the defender
+            let child_number = |(proof, disproof): (u64, u64)| {
+                if is_attacker { proof } else { disproof }
+            };
+            let best = (0..numbers.len())
+                .min_by_key(|&i| child_number(numbers[i]))
+                .unwrap();
+            let second_best_number = (0..numbers.len())
+                .filter(|&i| i != best)
+                .map(|i| child_number(numbers[i]))
+                .min()
+                .unwrap_or(PROOF_INFINITY);
+            let (child_proof, child_disproof) = numbers[best];
+            // The child is searched until it is no longer the most proving one, or until the parent reaches its thresholds
+            let child_thresholds = if is_attacker {
+                (
+                    u64::min(thresholds.0, second_best_number.saturating_add(1)),
+                    (thresholds.1 - disproof).saturating_add(child_disproof),
+                )
+            } else {
+                (
+                    (thresholds.0 - proof).saturating_add(child_proof),
+                    u64::min(thresholds.1, second_best_number.saturating_add(1)),
+                )
+            };
+            let (m, child, _) = &children[best];
+            self.line.push(m.clone());
+            self.search(child, child_thresholds);
+            self.line.pop();
+        }
+    }
+
+    fn solution_subtree(&mut self, board: &Board, proven: bool) -> MoveTree {
+        let mut tree = MoveTree {
+            children: Vec::new(),
+        };
+        if self.leaf_numbers(board).is_some() {
+            return tree;
+        }
+        let is_solution = |(proof, disproof): (u64, u64)| {
+            if proven { proof == 0 } else { disproof == 0 }
+        };
+        let children = children(board);
+        if self.is_attacker(board) == proven {
+            // A single move is needed: the table is checked before searching the evicted children again
+            let index = (0..children.len())
+                .find(|&i| is_solution(self.numbers(children[i].2)))
+                .or_else(|| {
+                    (0..children.len()).find(|&i| {
+                        is_solution(self.search(&children[i].1, (PROOF_INFINITY, PROOF_INFINITY)))
+                    })
+                })
+                .expect("The board is not solved");
+            let (m, child, _) = &children[index];
+            tree.children
+                .push((m.clone(), self.solution_subtree(child, proven)));
+        } else {
+            for (m, child, child_key) in children {
+                if !is_solution(self.numbers(child_key)) {
+                    let numbers = self.search(&child, (PROOF_INFINITY, PROOF_INFINITY));
+                    assert!(is_solution(numbers), "The board is not solved");
+                }
+                let subtree = self.solution_subtree(&child, proven);
+                tree.children.push((m, subtree));
+            }
+        }
+        tree
+    }
+
+    fn add_node(&mut self) {
+        self.nodes += 1;
+        if self.nodes.is_multiple_of(NODES_BETWEEN_PROGRESS) {
+            let root = self.numbers(self.root.exact_state_hash());
+            (self.on_progress)(&ProofProgress {
+                nodes: self.nodes,
+                root,
+                line: &self.line,
+            });
+        }
+    }
+
+    fn is_attacker(&self, board: &Board) -> bool {
+        board.get_player_is_white() == self.root.get_player_is_white()
+    }
+
+    /// Proof and disproof numbers of the ended games (and of the tablebase positions, if used)
+    fn leaf_numbers(&self, board: &Board) -> Option<(u64, u64)> {
+        // Current player POV: 1 for a win, 0 for a draw, -1 for a loss
+        let result = match board.get_win_status() {
+            Win(player) => {
+                if player.is_white() == board.get_player_is_white() {
+                    1
+                } else {
+                    -1
+                }
+            }
+            Draw => 0,
+            Continue => match probe_tablebase(board).filter(|_| self.use_tablebase)? {
+                TablebaseResult::Win(_) => 1,
+                TablebaseResult::Loss(_) => -1,
+                TablebaseResult::Draw => 0,
+            },
+        };
+        let attacker_result = if self.is_attacker(board) {
+            result
+        } else {
+            -result
+        };
+        let is_reached = match self.goal {
+            Goal::Win => attacker_result == 1,
+            Goal::AtLeastDraw => attacker_result >= 0,
+        };
+        if is_reached {
+            Some((0, PROOF_INFINITY))
+        } else {
+            Some((PROOF_INFINITY, 0))
+        }
+    }
+
+    /// Numbers of an unexplored node: (1, 1)
+    fn numbers(&self, key: u64) -> (u64, u64) {
+        self.table
+            .get(key)
+            .map_or((1, 1), |entry| (entry.proof, entry.disproof))
+    }
+
+    fn store(&mut self, key: u64, (proof, disproof): (u64, u64), work: u64) {
+        self.table.insert(
+            key,
+            ProofEntry {
+                proof,
+                disproof,
+                work,
+            },
+        );
+    }
+}
+
+/// Possible moves, with the boards they lead to and the keys of these boards
+fn children(board: &Board) -> Vec<(Move, Board, u64)> {
+    board
+        .possible_moves()
+        .into_iter()
+        .map(|m| {
+            let mut child = board.clone();
+            child.play(&m);
+            let key = child.exact_state_hash();
+            (m, child, key)
+        })
+        .collect()
+}
+
+/// Attacker: proven if one child is proven, disproven if all children are. Defender: conversely
+fn combine(is_attacker: bool, numbers: &[(u64, u64)]) -> (u64, u64) {
+    let min = |f: fn(&(u64, u64)) -> u64| numbers.iter().map(f).min().unwrap();
+    let sum = |f: fn(&(u64, u64)) -> u64| numbers.iter().map(f).fold(0, u64::saturating_add);
+    if is_attacker {
+        (min(|n| n.0), sum(|n| n.1))
+    } else {
+        (sum(|n| n.0), min(|n| n.1))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Goal, ProofNumberSearch};
+    use crate::checkers::board::{Board, MAX_MOVES_WITHOUT_CAPTURE};
+    use crate::checkers::pdn::{board_of_fen, move_of_pdn};
+    use crate::players::proof_number::proof_table::ProofTable;
+
+    fn prove(board: &Board, goal: Goal) -> bool {
+        let mut table = ProofTable::new(1);
+        ProofNumberSearch::new(board, goal, false, &mut table, |_| ()).prove()
+    }
+
+    #[test]
+    fn capture_wins() {
+        // Black captures the last white piece
+        let board = board_of_fen("B:W22:B18").unwrap();
+        assert!(prove(&board, Goal::Win));
+        let mut table = ProofTable::new(1);
+        let mut search = ProofNumberSearch::new(&board, Goal::Win, false, &mut table, |_| ());
+        search.prove();
+        let tree = search.solution_tree(true);
+        assert_eq!(tree.children.len(), 1);
+        assert_eq!(tree.children[0].0, move_of_pdn(&board, "18x25").unwrap());
+    }
+
+    #[test]
+    fn forced_capture_loses() {
+        // After either white move, black captures the last white piece
+        let board = board_of_fen("W:W26:B18").unwrap();
+        assert!(!prove(&board, Goal::AtLeastDraw));
+        let mut table = ProofTable::new(1);
+        let mut search =
+            ProofNumberSearch::new(&board, Goal::AtLeastDraw, false, &mut table, |_| ());
+        search.prove();
+        // A black reply against each white move
+        let tree = search.solution_tree(false);
+        assert_eq!(tree.children.len(), 2);
+        assert!(
+            tree.children
+                .iter()
+                .all(|(_, reply)| reply.children.len() == 1)
+        );
+    }
+
+    #[test]
+    fn forty_move_rule_draws() {
+        // Any move of a king reaches the 40-move draw
+        let board = board_of_fen("W:WK1:BK32")
+            .unwrap()
+            .with_moves_without_capture(MAX_MOVES_WITHOUT_CAPTURE - 1);
+        assert!(prove(&board, Goal::AtLeastDraw));
+        assert!(!prove(&board, Goal::Win));
+    }
+
+    #[test]
+    fn man_move_orders_share_keys() {
+        let transposed_board = |black_moves: [&str; 2]| {
+            let mut board = Board::new();
+            for m in [black_moves[0], "23-19", black_moves[1]] {
+                board.play(&move_of_pdn(&board, m).unwrap());
+            }
+            board
+        };
+        assert_eq!(
+            transposed_board(["9-13", "10-14"]).exact_state_hash(),
+            transposed_board(["10-14", "9-13"]).exact_state_hash()
+        );
+    }
+
+    #[test]
+    fn king_move_orders_do_not_share_keys() {
+        // The boards in between may be repeated later
+        let transposed_board = |first_king: &str, second_king: &str| {
+            let mut board = board_of_fen("B:WK29:BK1,K3").unwrap();
+            for m in [first_king, "29-25", second_king] {
+                board.play(&move_of_pdn(&board, m).unwrap());
+            }
+            board
+        };
+        let (board, other_board) = (
+            transposed_board("1-6", "3-8"),
+            transposed_board("3-8", "1-6"),
+        );
+        assert_eq!(board.state_hash(), other_board.state_hash());
+        assert_ne!(board.exact_state_hash(), other_board.exact_state_hash());
+    }
+}
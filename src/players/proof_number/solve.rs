@@ -0,0 +1,93 @@
+use crate::checkers::board::Board;
+use crate::checkers::pdn::{MoveTree, fen_of_board, pdn_game};
+use crate::consts::{SOLVE_PDN_FILE, SOLVE_TABLE_SIZE, SOLVE_USE_TABLEBASE};
+use crate::players::proof_number::proof_table::ProofTable;
+use crate::players::proof_number::search::{Goal, ProofNumberSearch};
+use std::fs;
+use std::time::Instant;
+
+// Solves a board with two proof-number searches: can the player to move reach at least a draw? If so, can they win?
+// The trees proving the result are written as PDN games.
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SolveResult {
+    Win,
+    Draw,
+    Loss,
+}
+
+pub fn run_solve(board: &Board) {
+    println!("{}", board);
+    println!("FEN: {}", fen_of_board(board));
+    let t0 = Instant::now();
+    let mut table = ProofTable::new(SOLVE_TABLE_SIZE);
+    let mut solutions = Vec::new();
+    let mut result = SolveResult::Win;
+    for goal in [Goal::AtLeastDraw, Goal::Win] {
+        table.clear();
+        let mut search =
+            ProofNumberSearch::new(board, goal, SOLVE_USE_TABLEBASE, &mut table, |progress| {
+                println!("> Solving ({}): {}, {:?}", goal, progress, t0.elapsed());
+            });
+        let proven = search.prove();
+        println!(
+            "> {} {}: {} nodes, {:?}",
+            if proven { "Proven" } else { "Disproven" },
+            goal,
+            search.get_nodes(),
+            t0.elapsed()
+        );
+        let tree = search.solution_tree(proven);
+        solutions.push((goal, proven, search.get_nodes(), tree));
+        if !proven {
+            result = match goal {
+                Goal::AtLeastDraw => SolveResult::Loss,
+                Goal::Win => SolveResult::Draw,
+            };
+            break;
+        }
+    }
+    println!("> Result for the player to move: {:?}", result);
+
+    let games = solutions
+        .iter()
+        .map(|(goal, proven, nodes, tree)| {
+            solution_game(board, result, *goal, *proven, *nodes, tree)
+        })
+        .collect::<Vec<_>>();
+    fs::write(SOLVE_PDN_FILE, games.join("\n")).unwrap();
+    println!("> Proof trees written to {}", SOLVE_PDN_FILE);
+}
+
+fn solution_game(
+    board: &Board,
+    result: SolveResult,
+    goal: Goal,
+    proven: bool,
+    nodes: u64,
+    tree: &MoveTree,
+) -> String {
+    // PDN colors are swapped (cf `src/checkers/pdn.rs`)
+    let (player, other_player, player_wins, player_loses) = if board.get_player_is_white() {
+        ("Black", "White", "0-2", "2-0")
+    } else {
+        ("White", "Black", "2-0", "0-2")
+    };
+    let pdn_result = match result {
+        SolveResult::Win => player_wins,
+        SolveResult::Draw => "1-1",
+        SolveResult::Loss => player_loses,
+    };
+    let comment = if proven {
+        format!(
+            "{} to move reaches {}: a move of {} against each reply ({} nodes)",
+            player, goal, player, nodes
+        )
+    } else {
+        format!(
+            "{} to move cannot reach {}: a reply of {} against each move ({} nodes)",
+            player, goal, other_player, nodes
+        )
+    };
+    pdn_game(&[("Event", "Solve")], board, &comment, tree, pdn_result)
+}
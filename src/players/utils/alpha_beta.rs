@@ -1,38 +1,544 @@
-use crate::checkers::board::{Board, BoardHash, Move};
-use crate::players::alpha_beta::score::{NEG_INFINITY, POS_INFINITY};
-use std::cmp::Reverse;
-use std::collections::HashMap;
+use crate::checkers::board::{Board, BoardHash, Move, string_of_move};
+use crate::consts::NB_SEARCH_THREADS;
+use crate::players::alpha_beta::score::{
+    NEG_INFINITY, POS_INFINITY, board_score_of_root_score, contempt_score, draw_score,
+    end_game_score, root_score_of_board_score,
+};
+use crate::players::utils::heuristic::Heuristic;
+use crate::players::utils::move_ordering::MoveOrdering;
+use crate::players::utils::tablebase::{TablebaseResult, probe_tablebase};
+use crate::players::utils::time_control::TimeBudget;
+use crate::players::utils::transposition_table::{Bound, TranspositionEntry, TranspositionTable};
+use std::fmt::{Display, Formatter};
+use std::num::NonZero;
+use std::ops::AddAssign;
 #[cfg(not(nn_is_sync))]
 use std::ops::Deref;
-use std::sync::Arc;
-use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicI64, AtomicU64, Ordering};
+use std::sync::{Arc, OnceLock};
 use std::thread;
+use std::time::{Duration, Instant};
+
+/// Search threads check the time and node limits after exploring this number of nodes
+const NODES_BETWEEN_CHECKS: u64 = 256;
 
 // heuristic_score: Current player point of view
 
-const MAX_THREADING_DEPTH: i8 = 1; // recommended: 1 or 2 (branch-size usually between 5 and 10)
-const BEST_MOVE_FIRST_MIN_DEPTH: i8 = 2 * 4; // use "best move first" strategy if depth >= BEST_MOVE_FIRST_MIN_DEPTH
-const BEST_MOVE_FIRST_SKIP_SIZE: i8 = 2 * 3; // choose the best move by exploring at depth := depth - BEST_MOVE_FIRST_SKIP_SIZE
+#[derive(Clone, Debug)]
+pub struct PrincipalVariation {
+    pub depth: i8,
+    /// Current player POV
+    pub score: i64,
+    pub moves: Vec<Move>,
+}
+
+impl Display for PrincipalVariation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let moves = self
+            .moves
+            .iter()
+            .map(string_of_move)
+            .collect::<Vec<_>>()
+            .join(", ");
+        write!(f, "depth {}, score {}: {}", self.depth, self.score, moves)
+    }
+}
+
+/// Search features, so that two versions of the search can be compared (cf `run_search_match`)
+#[derive(Clone, Copy, Debug)]
+pub struct SearchParameters {
+    /// Maximum number of capture positions explored after depth 0, per leaf. 0 disables the quiescence search
+    pub quiescence_nodes: u32,
+    /// Principal Variation Search: moves after the first one are searched with a null window first
+    pub principal_variation_search: bool,
+    /// Each iteration of the iterative deepening searches around the previous score, with this margin. 0 disables it
+    pub aspiration_window: i64,
+    /// Maximum number of plies added to a line by the extensions (cf `extension`). 0 disables them
+    pub max_extensions: i8,
+    /// Score lost by the searching player when the game ends in a draw (cf `draw_score`). 0 scores draws as even
+    pub contempt: i64,
+}
+
+impl SearchParameters {
+    /// Key of `board` in the transposition table, for a search from `root`.
+    /// With contempt, the stored scores depend on the contempt and on the root player (cf `draw_score`):
+    /// they get other keys, so that they are never read by another search (e.g. an analysis without contempt)
+    pub fn transposition_key(&self, root: &Board, board: &Board) -> u64 {
+        board.state_hash() ^ self.transposition_salt(root)
+    }
+
+    fn transposition_salt(&self, root: &Board) -> u64 {
+        if self.contempt == 0 {
+            return 0;
+        }
+        // Multiplying by an odd constant is a bijection: two different contempts or root players never get the same salt
+        ((self.contempt as u64) << 1 | u64::from(root.get_player_is_white()))
+            .wrapping_mul(0x9e37_79b9_7f4a_7c15)
+    }
+}
+
+/// Limits of a search. The search stops as soon as one of them is reached, and never stops without any
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SearchLimits {
+    /// Maximum depth of the iterative deepening
+    pub depth: Option<i8>,
+    /// Maximum number of explored nodes, summed over the search threads
+    pub nodes: Option<u64>,
+    pub time: Option<TimeBudget>,
+}
+
+/// Stops a search: on demand (e.g. from another thread), or once its time or node limit is reached.
+/// The limits are checked inside the search, by every search thread.
+pub struct StopCondition {
+    stopped: AtomicBool,
+    /// Start of the time limit, and its budget
+    clock: OnceLock<(Instant, TimeBudget)>,
+    max_nodes: Option<u64>,
+    /// Nodes explored by every search thread, updated every `NODES_BETWEEN_CHECKS` nodes
+    nodes: AtomicU64,
+}
+
+impl StopCondition {
+    /// The time limit starts now
+    pub fn new(limits: SearchLimits) -> Self {
+        let clock = OnceLock::new();
+        if let Some(budget) = limits.time {
+            clock.set((Instant::now(), budget)).unwrap();
+        }
+        StopCondition {
+            stopped: AtomicBool::new(false),
+            clock,
+            max_nodes: limits.nodes,
+            nodes: AtomicU64::new(0),
+        }
+    }
+
+    /// Starts now the time limit of a search created without one (e.g. on a ponder hit, cf `Ponder::hit`).
+    /// Without effect if the time limit has already started
+    pub fn start_clock(&self, budget: TimeBudget) {
+        let _ = self.clock.set((Instant::now(), budget));
+    }
+
+    /// Time elapsed since the start of the time limit, and its budget
+    pub fn clock(&self) -> Option<(Duration, TimeBudget)> {
+        self.clock
+            .get()
+            .map(|(start, budget)| (start.elapsed(), *budget))
+    }
+
+    pub fn stop(&self) {
+        self.stopped.store(true, Ordering::Release);
+    }
+
+    pub fn is_stopped(&self) -> bool {
+        self.stopped.load(Ordering::Acquire)
+    }
+
+    fn add_nodes(&self, nodes: u64) {
+        let total_nodes = self.nodes.fetch_add(nodes, Ordering::Relaxed) + nodes;
+        if self
+            .max_nodes
+            .is_some_and(|max_nodes| total_nodes >= max_nodes)
+            || self
+                .clock()
+                .is_some_and(|(elapsed, budget)| elapsed >= budget.maximum)
+        {
+            self.stop();
+        }
+    }
+}
+
+/// Counters of a search, summed over its threads
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SearchCounters {
+    /// Number of explored nodes
+    pub nodes: u64,
+    /// Number of transposition table lookups
+    pub transposition_probes: u64,
+    /// Number of transposition table lookups which found an entry
+    pub transposition_hits: u64,
+}
+
+impl AddAssign for SearchCounters {
+    fn add_assign(&mut self, other: Self) {
+        self.nodes += other.nodes;
+        self.transposition_probes += other.transposition_probes;
+        self.transposition_hits += other.transposition_hits;
+    }
+}
+
+/// Progress of a search, sent after each iteration of the iterative deepening
+#[derive(Clone, Debug)]
+pub struct SearchInfo {
+    pub principal_variation: PrincipalVariation,
+    /// Since the beginning of the search
+    pub counters: SearchCounters,
+    /// Since the beginning of the search
+    pub elapsed: Duration,
+}
+
+impl SearchInfo {
+    pub fn nodes_per_second(&self) -> u64 {
+        (self.counters.nodes as f64 / self.elapsed.as_secs_f64().max(1e-3)) as u64
+    }
+
+    /// Ratio of the transposition table lookups which found an entry
+    pub fn transposition_hit_rate(&self) -> f64 {
+        self.counters.transposition_hits as f64
+            / u64::max(1, self.counters.transposition_probes) as f64
+    }
+}
+
+impl Display for SearchInfo {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} ({} nodes, {} nodes/s, {:.1}% cache hits, {:.2?})",
+            self.principal_variation,
+            self.counters.nodes,
+            self.nodes_per_second(),
+            100. * self.transposition_hit_rate(),
+            self.elapsed
+        )
+    }
+}
+
+pub struct SearchResult {
+    pub best_move: Move,
+    /// One principal variation per completed iteration, the deepest being the last one
+    pub principal_variations: Vec<PrincipalVariation>,
+    /// Number of explored nodes
+    pub nodes: u64,
+}
+
+/// State of a single search thread
+struct SearchContext<'a, H: Heuristic> {
+    heuristic_score: &'a H,
+    parameters: SearchParameters,
+    transposition_table: &'a TranspositionTable,
+    /// Mixed into the transposition table keys (cf `SearchParameters::transposition_key`)
+    transposition_salt: u64,
+    stop_condition: &'a StopCondition,
+    /// Set once the main thread completes the current iteration, so that the helper threads stop
+    iteration_done: &'a AtomicBool,
+    /// Kept between the iterations of the iterative deepening (cf `alpha_beta_moves_list`)
+    move_ordering: &'a mut MoveOrdering,
+    counters: SearchCounters,
+    /// Plies added by the extensions to the line being searched
+    extensions: i8,
+    /// Heuristic scores of the leaves of the nodes being explored, by state hash (cf `batch_leaf_scores`)
+    leaf_scores: Vec<(u64, i64)>,
+    /// Heuristic states of the boards of the line being searched, the last one being the current board (cf `play`)
+    states: Vec<H::State>,
+    /// Positions of the boards of the line being searched, from the root to the current board (cf `play`)
+    line: Vec<BoardHash>,
+    /// The scores found so far in the current node depend on the boards of the line before this ply, which they repeat
+    /// (cf `note_repetition`): 0 for the boards before the root, `usize::MAX` for none
+    history_ply: usize,
+}
+
+impl<'a, H: Heuristic> SearchContext<'a, H> {
+    /// `board` is the root of the search
+    fn new(
+        board: &Board,
+        heuristic_score: &'a H,
+        parameters: SearchParameters,
+        transposition_table: &'a TranspositionTable,
+        stop_condition: &'a StopCondition,
+        iteration_done: &'a AtomicBool,
+        move_ordering: &'a mut MoveOrdering,
+    ) -> Self {
+        SearchContext {
+            heuristic_score,
+            parameters,
+            transposition_table,
+            transposition_salt: parameters.transposition_salt(board),
+            stop_condition,
+            iteration_done,
+            move_ordering,
+            counters: SearchCounters::default(),
+            extensions: 0,
+            leaf_scores: Vec::new(),
+            states: vec![heuristic_score.state(board)],
+            line: vec![board.hash()],
+            history_ply: usize::MAX,
+        }
+    }
+
+    /// Returns the child of `board` (the current board) reached by `m`, which becomes the current board until `undo`
+    fn play(&mut self, board: &Board, m: &Move) -> Board {
+        let mut child_board = board.clone();
+        child_board.play(m);
+        let state = self
+            .heuristic_score
+            .play(self.states.last().unwrap(), board, &child_board);
+        self.states.push(state);
+        self.line.push(child_board.hash());
+        child_board
+    }
+
+    /// Same as `SearchParameters::transposition_key`, for the root of this search
+    fn transposition_key(&self, board: &Board) -> u64 {
+        board.state_hash() ^ self.transposition_salt
+    }
+
+    /// Goes back to the parent of the current board (cf `play`)
+    fn undo(&mut self) {
+        self.states.pop();
+        self.line.pop();
+    }
+
+    /// Transposition keys only count the repetitions of their own board (cf `Board::state_hash`).
+    /// If `board`, `ply` plies after the root, repeats earlier boards, the scores depending on it also depend on them,
+    /// so the nodes after the first of them are not stored (cf `history_ply`)
+    fn note_repetition(&mut self, board: &Board, ply: usize) {
+        let board_count = board.get_board_count() as usize;
+        if board_count < 2 {
+            return;
+        }
+        let hash = board.hash();
+        let earlier_plies = (0..ply).filter(|&earlier_ply| self.line[earlier_ply] == hash);
+        let history_ply = match earlier_plies.clone().next() {
+            Some(first_ply) if earlier_plies.count() + 1 == board_count => first_ply + 1,
+            // Reached before the root
+            _ => 0,
+        };
+        self.history_ply = usize::min(self.history_ply, history_ply);
+    }
+
+    fn is_cancelled(&self) -> bool {
+        self.stop_condition.is_stopped() || self.iteration_done.load(Ordering::Acquire)
+    }
+
+    fn add_node(&mut self) {
+        self.counters.nodes += 1;
+        if self.counters.nodes.is_multiple_of(NODES_BETWEEN_CHECKS) {
+            self.stop_condition.add_nodes(NODES_BETWEEN_CHECKS);
+        }
+    }
+
+    /// Plies added to the depth of `child_board`, a child of `board`: one for the only legal move, or for a capture
+    /// the other player has to answer with a capture, as long as the line has not used all its extensions.
+    /// Forced moves would otherwise use up the depth and hide the tactics behind them
+    fn extension(&self, nb_moves: usize, board: &Board, child_board: &Board) -> i8 {
+        // Not the number of moves without capture, which promotions reset too
+        let is_capture = child_board.get_nb_pieces() < board.get_nb_pieces();
+        let is_ongoing_capture = is_capture && child_board.can_jump();
+        i8::from(
+            self.extensions < self.parameters.max_extensions
+                && (nb_moves == 1 || is_ongoing_capture),
+        )
+    }
+
+    /// Heuristic score of a board `ply` plies after the root, with contempt (cf `contempt_score`)
+    fn leaf_score(&self, board: &Board, ply: usize) -> i64 {
+        let state_hash = board.state_hash();
+        let heuristic_score = self
+            .leaf_scores
+            .iter()
+            .rev()
+            .find(|(leaf_hash, _)| *leaf_hash == state_hash)
+            .map_or_else(
+                || {
+                    self.heuristic_score
+                        .score_with_state(board, self.states.last().unwrap())
+                },
+                |&(_, score)| score,
+            );
+        heuristic_score + contempt_score(board, ply, self.parameters.contempt)
+    }
+
+    /// With a batched heuristic, scores together the children of a node at depth 1 which are leaves (cf `leaf_score`).
+    /// Some of them may then be pruned: they are scored for nothing, but a batch is much faster than scoring them one by one.
+    /// The scores are kept until `leaf_scores` is truncated back to its returned length
+    fn batch_leaf_scores(&mut self, board: &Board, moves: &[(usize, Move)], depth: i8) -> usize {
+        if depth != 1 {
+            return self.leaf_scores.len();
+        }
+        self.batch_children_scores(
+            board,
+            moves.iter().map(|(_, m)| m),
+            |context, child_board| {
+                context.extension(moves.len(), board, child_board) == 0
+                    && (context.parameters.quiescence_nodes == 0 || !child_board.can_jump())
+            },
+        )
+    }
+
+    /// Same as `batch_leaf_scores`, for the children of a quiescence node: the ones where no capture is forced,
+    /// or all of them once no quiescence node is left
+    fn batch_quiescence_leaf_scores(
+        &mut self,
+        board: &Board,
+        moves: &[Move],
+        nodes_left: u32,
+    ) -> usize {
+        self.batch_children_scores(board, moves.iter(), |_, child_board| {
+            nodes_left == 0 || !child_board.can_jump()
+        })
+    }
 
+    /// Scores together the children of `board` for which `is_leaf`, unless they end the game or are in the tablebase
+    fn batch_children_scores<'m>(
+        &mut self,
+        board: &Board,
+        moves: impl Iterator<Item = &'m Move>,
+        is_leaf: impl Fn(&Self, &Board) -> bool,
+    ) -> usize {
+        let nb_leaf_scores = self.leaf_scores.len();
+        if !self.heuristic_score.is_batched() {
+            return nb_leaf_scores;
+        }
+        let leaves = moves
+            .map(|m| {
+                let mut cloned_board = board.clone();
+                cloned_board.play(m);
+                cloned_board
+            })
+            .filter(|child_board| {
+                !child_board.is_end_game()
+                    && probe_tablebase(child_board).is_none()
+                    && is_leaf(self, child_board)
+            })
+            .collect::<Vec<_>>();
+        let scores = self.heuristic_score.scores(&leaves);
+        self.leaf_scores
+            .extend(leaves.iter().map(Board::state_hash).zip(scores));
+        nb_leaf_scores
+    }
+
+    /// Tablebase draws are scored with contempt, like the ended games
+    fn tablebase_score(&self, result: TablebaseResult, ply: usize) -> i64 {
+        match result {
+            TablebaseResult::Draw => draw_score(self.parameters.contempt, ply),
+            _ => root_score_of_board_score(result.score(), ply),
+        }
+    }
+}
+
+/// Returns the best moves and their score (None if the search is stopped), and the search counters.
+/// Scores outside of `window` (alpha, beta) are not exact: they are only bounds.
+/// A threaded search runs `NB_SEARCH_THREADS` threads (cf `parallel_best_moves`).
+/// `move_orderings` holds the killer moves and history of each thread, and is kept between the iterations of the
+/// iterative deepening: it is filled as needed, starting from an empty `Vec`
+#[allow(clippy::too_many_arguments)]
 pub fn alpha_beta_moves_list(
     board: &Board,
     // TODO: Use a pub type `Arc<...>` everywhere when being part of stable Rust
-    #[cfg(nn_is_sync)] heuristic_score: Arc<impl Fn(&Board) -> i64 + Send + Sync + 'static>,
-    #[cfg(not(nn_is_sync))] heuristic_score: Arc<impl Fn(&Board) -> i64 + Send + Clone + 'static>,
+    #[cfg(nn_is_sync)] heuristic_score: &Arc<impl Heuristic + Send + Sync + 'static>,
+    #[cfg(not(nn_is_sync))] heuristic_score: &Arc<impl Heuristic + Send + Clone + 'static>,
+    parameters: SearchParameters,
     depth: i8,
+    window: (i64, i64),
     threaded: bool,
-    cancel_search: Arc<AtomicBool>,
-) -> Option<Vec<Move>> {
-    if threaded {
-        threaded_best_moves(
-            board.to_owned(),
-            heuristic_score,
-            depth,
-            MAX_THREADING_DEPTH,
-            cancel_search,
-        )
-    } else {
-        alpha_beta_best_moves(board, &*heuristic_score, depth, &cancel_search)
+    stop_condition: &StopCondition,
+    transposition_table: &TranspositionTable,
+    move_orderings: &mut Vec<MoveOrdering>,
+) -> (Option<(Vec<Move>, i64)>, SearchCounters) {
+    let nb_threads = if threaded { nb_search_threads() } else { 1 };
+    parallel_best_moves(
+        board,
+        heuristic_score,
+        parameters,
+        depth,
+        window,
+        nb_threads,
+        stop_condition,
+        transposition_table,
+        move_orderings,
+    )
+}
+
+/// Returns the `nb_lines` best moves with their exact score, best first (None if the search is stopped), and the search counters.
+/// The other moves are only proven worse than them. The moves of `previous_lines` are explored first, in this order.
+/// Single-threaded, since it is meant for analysis. `move_ordering` is kept between the iterations of the iterative deepening.
+#[allow(clippy::too_many_arguments)]
+pub fn multi_pv_moves_list(
+    board: &Board,
+    heuristic_score: &impl Heuristic,
+    parameters: SearchParameters,
+    depth: i8,
+    nb_lines: usize,
+    previous_lines: &[Move],
+    stop_condition: &StopCondition,
+    transposition_table: &TranspositionTable,
+    move_ordering: &mut MoveOrdering,
+) -> (Option<Vec<(Move, i64)>>, SearchCounters) {
+    let iteration_done = AtomicBool::new(false);
+    let mut context = SearchContext::new(
+        board,
+        heuristic_score,
+        parameters,
+        transposition_table,
+        stop_condition,
+        &iteration_done,
+        move_ordering,
+    );
+    let mut moves = board.possible_moves();
+    moves.sort_by_key(|m| {
+        previous_lines
+            .iter()
+            .position(|previous_move| previous_move == m)
+            .unwrap_or(usize::MAX)
+    });
+
+    let mut lines: Vec<(Move, i64)> = Vec::new();
+    for m in moves {
+        // Only moves better than the worst kept line need an exact score
+        let alpha = if lines.len() < nb_lines {
+            NEG_INFINITY
+        } else {
+            lines[nb_lines - 1].1 - 1
+        };
+        let cloned_board = context.play(board, &m);
+        let res = -alpha_beta_score(
+            &cloned_board,
+            -POS_INFINITY,
+            -alpha,
+            depth - 1,
+            1,
+            &mut context,
+        );
+        context.undo();
+        if res > alpha {
+            let position = lines.partition_point(|(_, score)| *score >= res);
+            lines.insert(position, (m, res));
+            lines.truncate(nb_lines);
+        }
+    }
+
+    if context.is_cancelled() {
+        return (None, context.counters);
+    }
+    (Some(lines), context.counters)
+}
+
+/// Follows the best moves stored in the transposition table, starting with `first_move`.
+/// Stops at a best move which is not legal, e.g. from an entry of another position with the same key
+pub fn principal_variation(
+    root: &Board,
+    first_move: &Move,
+    parameters: SearchParameters,
+    transposition_table: &TranspositionTable,
+    depth: i8,
+    score: i64,
+) -> PrincipalVariation {
+    let mut board = root.clone();
+    let mut moves = vec![first_move.clone()];
+    board.play(first_move);
+    while moves.len() < depth as usize
+        && !board.is_end_game()
+        && let Some(entry) = transposition_table.get(parameters.transposition_key(root, &board))
+        && let Some(best_move) = entry.best_move
+        && let Some(m) = board.possible_moves().get(best_move).cloned()
+    {
+        board.play(&m);
+        moves.push(m);
+    }
+    PrincipalVariation {
+        depth,
+        score,
+        moves,
     }
 }
 
@@ -40,286 +546,895 @@ fn alpha_beta_score(
     board: &Board,
     alpha: i64,
     beta: i64,
-    heuristic_score: &impl Fn(&Board) -> i64,
-    cache: &mut HashMap<(BoardHash, i8), (i64, i64)>,
     depth: i8,
-    cancel_search: &AtomicBool,
+    ply: usize,
+    context: &mut SearchContext<impl Heuristic>,
 ) -> i64 {
-    if cancel_search.load(Ordering::Acquire) {
+    if context.is_cancelled() {
         return 0;
     }
-    if depth == 0 || board.is_end_game() {
-        return heuristic_score(board);
+    context.add_node();
+    context.note_repetition(board, ply);
+    if board.is_end_game() {
+        return end_game_score(board, ply, context.parameters.contempt);
+    }
+    if let Some(result) = probe_tablebase(board) {
+        return context.tablebase_score(result, ply);
+    }
+    if depth == 0 {
+        let mut nodes_left = context.parameters.quiescence_nodes;
+        return quiescence_score(board, alpha, beta, ply, &mut nodes_left, context);
     }
 
-    // ********** Import cache results **********
+    // ********** Import cached results **********
     let (mut alpha, mut beta) = (alpha, beta);
-    if let Some(&(min, max)) = cache.get(&(board.hash(), depth)) {
-        if max <= alpha {
-            return alpha;
-        }
-        if min >= beta {
-            return beta;
+    let entry_opt = context
+        .transposition_table
+        .get(context.transposition_key(board));
+    context.counters.transposition_probes += 1;
+    if entry_opt.is_some() {
+        context.counters.transposition_hits += 1;
+    }
+    if let Some(entry) = entry_opt
+        && entry.depth >= depth
+    {
+        let score = root_score_of_board_score(entry.score, ply);
+        match entry.bound {
+            Bound::Exact => return score,
+            Bound::Lower => alpha = i64::max(alpha, score),
+            Bound::Upper => beta = i64::min(beta, score),
         }
-        if min == max {
-            return min;
+        if alpha >= beta {
+            return score;
         }
-        alpha = i64::max(alpha, min);
-        beta = i64::min(beta, max);
     }
     let (alpha_init, beta_init) = (alpha, beta);
 
     // ********** Main: get score value **********
-    let moves = best_move_first(board, heuristic_score, cache, depth, cancel_search);
-    for m in moves {
-        let mut cloned_board = board.clone();
-        cloned_board.play(&m);
-        let res = -alpha_beta_score(
+    let moves =
+        context
+            .move_ordering
+            .order_moves(board, ply, entry_opt.and_then(|entry| entry.best_move));
+    let nb_moves = moves.len();
+    let nb_leaf_scores = context.batch_leaf_scores(board, &moves, depth);
+    let parent_history_ply = std::mem::replace(&mut context.history_ply, usize::MAX);
+    let mut best_move = None;
+    for (position, (index, m)) in moves.into_iter().enumerate() {
+        let cloned_board = context.play(board, &m);
+        let extension = context.extension(nb_moves, board, &cloned_board);
+        context.extensions += extension;
+        let res = child_score(
             &cloned_board,
-            -beta,
-            -alpha,
-            heuristic_score,
-            cache,
-            depth - 1,
-            cancel_search,
+            alpha,
+            beta,
+            depth - 1 + extension,
+            ply + 1,
+            position == 0,
+            context,
         );
+        context.extensions -= extension;
+        context.undo();
 
         // ********** Alpha-beta pruning **********
         if res >= beta {
             alpha = beta;
+            best_move = Some(index);
+            context.move_ordering.add_cutoff(board, ply, &m, depth);
             break;
         } else if res > alpha {
             alpha = res;
+            best_move = Some(index);
         }
     }
+    context.leaf_scores.truncate(nb_leaf_scores);
+    let is_storable = ply < context.history_ply;
+    context.history_ply = usize::min(parent_history_ply, context.history_ply);
 
-    if cancel_search.load(Ordering::Acquire) {
+    if context.is_cancelled() {
         return 0;
     }
 
     let score = alpha;
+    if !is_storable {
+        return score;
+    }
     // ********** Store results **********
-    cache.insert((board.hash(), depth), {
-        let (stored_min, stored_max) = cache
-            .get(&(board.hash(), depth))
-            .copied()
-            .unwrap_or((NEG_INFINITY, POS_INFINITY));
-        if score <= alpha_init {
-            (stored_min, i64::min(stored_max, alpha_init))
-        } else if score >= beta_init {
-            (i64::max(stored_min, beta_init), stored_max)
-        } else {
-            (score, score)
-        }
-    });
+    let bound = if score <= alpha_init {
+        Bound::Upper
+    } else if score >= beta_init {
+        Bound::Lower
+    } else {
+        Bound::Exact
+    };
+    context.transposition_table.insert(
+        context.transposition_key(board),
+        TranspositionEntry {
+            score: board_score_of_root_score(score, ply),
+            depth,
+            bound,
+            best_move: best_move.or(entry_opt.and_then(|entry| entry.best_move)),
+        },
+    );
 
     score
 }
 
+/// Score of a child board, from the parent POV.
+/// With PVS, every move after the first one is expected to be worse than alpha: a null-window search checks it,
+/// and the move is searched again with the whole window only if it is better.
+fn child_score(
+    child_board: &Board,
+    alpha: i64,
+    beta: i64,
+    depth: i8,
+    ply: usize,
+    is_first_move: bool,
+    context: &mut SearchContext<impl Heuristic>,
+) -> i64 {
+    if context.parameters.principal_variation_search && !is_first_move {
+        let res = -alpha_beta_score(child_board, -alpha - 1, -alpha, depth, ply, context);
+        if res <= alpha || res >= beta {
+            return res;
+        }
+    }
+    -alpha_beta_score(child_board, -beta, -alpha, depth, ply, context)
+}
+
+/// Keeps exploring forced captures, so that the heuristic is not computed in the middle of an exchange
+fn quiescence_score(
+    board: &Board,
+    alpha: i64,
+    beta: i64,
+    ply: usize,
+    nodes_left: &mut u32,
+    context: &mut SearchContext<impl Heuristic>,
+) -> i64 {
+    if board.is_end_game() {
+        return end_game_score(board, ply, context.parameters.contempt);
+    }
+    if let Some(result) = probe_tablebase(board) {
+        return context.tablebase_score(result, ply);
+    }
+    if *nodes_left == 0 || !board.can_jump() {
+        return context.leaf_score(board, ply);
+    }
+    *nodes_left -= 1;
+    context.add_node();
+
+    // Captures are mandatory: there is no "stand pat" option, every capture has to be explored
+    let moves = board.possible_moves();
+    let nb_leaf_scores = context.batch_quiescence_leaf_scores(board, &moves, *nodes_left);
+    let mut alpha = alpha;
+    for m in moves {
+        let cloned_board = context.play(board, &m);
+        let res = -quiescence_score(&cloned_board, -beta, -alpha, ply + 1, nodes_left, context);
+        context.undo();
+        if res >= beta {
+            alpha = beta;
+            break;
+        } else if res > alpha {
+            alpha = res;
+        }
+    }
+    context.leaf_scores.truncate(nb_leaf_scores);
+    alpha
+}
+
+/// Scores outside of `window` are not exact: they are only bounds.
+/// `root_alpha` is shared by every thread searching this root at this depth: it is the best exact score found so far.
+/// A move failing low against it is worse than the best move of another thread, so it is ignored.
+/// Helper threads explore the moves in a different order (starting with the `first_move`-th one).
 fn alpha_beta_best_moves(
     board: &Board,
-    heuristic_score: &impl Fn(&Board) -> i64,
     depth: i8,
-    cancel_search: &AtomicBool,
-) -> Option<Vec<Move>> {
-    if cancel_search.load(Ordering::Acquire) {
+    window: (i64, i64),
+    root_alpha: &AtomicI64,
+    first_move: usize,
+    context: &mut SearchContext<impl Heuristic>,
+) -> Option<(Vec<Move>, i64)> {
+    if context.is_cancelled() {
         return None;
     }
 
-    if depth == 0 || board.is_end_game() {
-        return Some(Vec::new());
+    if board.is_end_game() {
+        return Some((
+            Vec::new(),
+            end_game_score(board, 0, context.parameters.contempt),
+        ));
+    }
+    if depth == 0 {
+        return Some((Vec::new(), context.leaf_score(board, 0)));
     }
 
-    let mut cache = HashMap::new();
-
-    let moves = best_move_first(board, heuristic_score, &mut cache, depth, cancel_search);
+    let best_move = context
+        .transposition_table
+        .get(context.transposition_key(board))
+        .and_then(|entry| entry.best_move);
+    let mut moves = context.move_ordering.order_moves(board, 0, best_move);
+    let nb_moves = moves.len();
+    moves.rotate_left(first_move % nb_moves);
 
+    let (window_alpha, beta) = window;
     let mut best_score = NEG_INFINITY;
     let mut best_moves = Vec::new();
-    for m in moves {
-        let mut cloned_board = board.clone();
-        cloned_board.play(&m);
-        let alpha = i64::max(best_score - 1, NEG_INFINITY);
-        let beta = POS_INFINITY;
-        let res = -alpha_beta_score(
+    for (position, (_, m)) in moves.into_iter().enumerate() {
+        let cloned_board = context.play(board, &m);
+        // Moves as good as the best one are kept too
+        let own_alpha = i64::max(best_score - 1, window_alpha);
+        let mut alpha = i64::max(own_alpha, root_alpha.load(Ordering::Relaxed) - 1);
+        let mut res = child_score(
             &cloned_board,
-            -beta,
-            -alpha,
-            heuristic_score,
-            &mut cache,
+            alpha,
+            beta,
             depth - 1,
-            cancel_search,
+            1,
+            position == 0,
+            context,
         );
+        if res <= alpha && alpha > own_alpha {
+            // Only an upper bound: the move is ignored, unless this thread has no move yet
+            if !best_moves.is_empty() {
+                context.undo();
+                continue;
+            }
+            alpha = own_alpha;
+            res = child_score(
+                &cloned_board,
+                alpha,
+                beta,
+                depth - 1,
+                1,
+                position == 0,
+                context,
+            );
+        }
+        context.undo();
         if res > best_score {
             best_score = res;
             best_moves = vec![m.clone()];
+            if res > alpha && res < beta {
+                // Exact score, which other threads can rely on
+                root_alpha.fetch_max(res, Ordering::Relaxed);
+            }
         } else if res == best_score {
             best_moves.push(m.clone());
         }
+        if best_score >= beta {
+            break;
+        }
     }
 
-    if cancel_search.load(Ordering::Acquire) {
+    if context.is_cancelled() {
         return None;
     }
 
-    Some(best_moves)
+    Some((best_moves, best_score))
 }
 
-fn threaded_score(
-    board: Board,
-    #[cfg(nn_is_sync)] heuristic_score: Arc<impl Fn(&Board) -> i64 + Send + Sync + 'static>,
-    #[cfg(not(nn_is_sync))] heuristic_score: Arc<impl Fn(&Board) -> i64 + Send + Clone + 'static>,
+/// Lazy SMP: every thread searches the whole tree, and they share the transposition table and the root alpha.
+/// The helper threads mostly fill the transposition table, which speeds up the main thread. Their results are discarded.
+#[allow(clippy::too_many_arguments)]
+fn parallel_best_moves(
+    board: &Board,
+    #[cfg(nn_is_sync)] heuristic_score: &Arc<impl Heuristic + Send + Sync + 'static>,
+    #[cfg(not(nn_is_sync))] heuristic_score: &Arc<impl Heuristic + Send + Clone + 'static>,
+    parameters: SearchParameters,
     depth: i8,
-    threads_depth: i8,
-    cancel_search: Arc<AtomicBool>,
-) -> i64 {
-    if cancel_search.load(Ordering::Acquire) {
-        return 0;
+    window: (i64, i64),
+    nb_threads: usize,
+    stop_condition: &StopCondition,
+    transposition_table: &TranspositionTable,
+    move_orderings: &mut Vec<MoveOrdering>,
+) -> (Option<(Vec<Move>, i64)>, SearchCounters) {
+    let iteration_done = AtomicBool::new(false);
+    let root_alpha = AtomicI64::new(NEG_INFINITY);
+    if move_orderings.len() < nb_threads {
+        move_orderings.resize_with(nb_threads, MoveOrdering::new);
     }
+    let (move_ordering, helper_move_orderings) = move_orderings.split_first_mut().unwrap();
+    thread::scope(|scope| {
+        let mut handle = Vec::new();
+        for (helper, move_ordering) in (1..nb_threads).zip(helper_move_orderings) {
+            let heuristic_score = cfg_select! {
+                nn_is_sync => heuristic_score.clone(),
+                not(nn_is_sync) => heuristic_score.deref().to_owned(),
+            };
+            let (iteration_done, root_alpha) = (&iteration_done, &root_alpha);
+            handle.push(scope.spawn(move || {
+                let heuristic_score = cfg_select! {
+                    nn_is_sync => heuristic_score,
+                    not(nn_is_sync) => Arc::new(heuristic_score),
+                };
+                let mut context = SearchContext::new(
+                    board,
+                    &*heuristic_score,
+                    parameters,
+                    transposition_table,
+                    stop_condition,
+                    iteration_done,
+                    move_ordering,
+                );
+                alpha_beta_best_moves(board, depth, window, root_alpha, helper, &mut context);
+                context.counters
+            }));
+        }
 
-    let mut cache = HashMap::new();
+        let mut context = SearchContext::new(
+            board,
+            &**heuristic_score,
+            parameters,
+            transposition_table,
+            stop_condition,
+            &iteration_done,
+            move_ordering,
+        );
+        let best_moves = alpha_beta_best_moves(board, depth, window, &root_alpha, 0, &mut context);
+        iteration_done.store(true, Ordering::Release);
 
-    if threads_depth == 0 {
-        return alpha_beta_score(
-            &board,
-            NEG_INFINITY,
-            POS_INFINITY,
-            &*heuristic_score,
-            &mut cache,
+        let mut counters = context.counters;
+        for h in handle {
+            counters += h.join().unwrap();
+        }
+        (best_moves, counters)
+    })
+}
+
+fn nb_search_threads() -> usize {
+    if NB_SEARCH_THREADS == 0 {
+        thread::available_parallelism().map_or(1, NonZero::get)
+    } else {
+        NB_SEARCH_THREADS
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        SearchContext, SearchLimits, SearchParameters, StopCondition, alpha_beta_best_moves,
+        alpha_beta_moves_list, alpha_beta_score, multi_pv_moves_list, principal_variation,
+    };
+    use crate::checkers::board::{Board, Move};
+    use crate::checkers::pdn::board_of_fen;
+    use crate::consts::SEARCH_PARAMETERS;
+    use crate::neural_network::neural_network::generate_neural_networks;
+    use crate::players::alpha_beta::bench::{TACTICAL_POSITIONS, solves_tactic};
+    use crate::players::alpha_beta::get_move::simple_heuristic;
+    use crate::players::alpha_beta::score::{DRAW, NEG_INFINITY, POS_INFINITY, win_score};
+    use crate::players::neural_network::get_move::neural_network_heuristic;
+    use crate::players::utils::heuristic::Heuristic;
+    use crate::players::utils::move_ordering::MoveOrdering;
+    use crate::players::utils::transposition_table::{
+        Bound, TranspositionEntry, TranspositionTable,
+    };
+    use crate::random::seeded_rng;
+    use std::cell::Cell;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicBool, AtomicI64, Ordering};
+
+    const DEPTH: i8 = 4;
+    /// Without contempt, draws score the same for both players, whatever the ply of the root
+    const PARAMETERS: SearchParameters = SearchParameters {
+        contempt: 0,
+        ..SEARCH_PARAMETERS
+    };
+    const CONTEMPT: i64 = 50_000;
+
+    fn score(board: &Board, transposition_table: &TranspositionTable) -> i64 {
+        score_at_depth(board, transposition_table, DEPTH)
+    }
+
+    fn score_at_depth(board: &Board, transposition_table: &TranspositionTable, depth: i8) -> i64 {
+        score_with_parameters(board, transposition_table, depth, PARAMETERS)
+    }
+
+    /// `simple_heuristic`, scoring the leaves in batches
+    #[derive(Default)]
+    struct BatchedHeuristic {
+        nb_batched_boards: Cell<usize>,
+        /// Boards scored on their own, out of the batches
+        nb_single_boards: Cell<usize>,
+    }
+
+    impl Heuristic for BatchedHeuristic {
+        type State = ();
+
+        fn score(&self, board: &Board) -> i64 {
+            self.nb_single_boards.set(self.nb_single_boards.get() + 1);
+            simple_heuristic(board)
+        }
+
+        fn state(&self, _board: &Board) {}
+
+        fn play(&self, _state: &(), _board: &Board, _child_board: &Board) {}
+
+        fn scores(&self, boards: &[Board]) -> Vec<i64> {
+            self.nb_batched_boards
+                .set(self.nb_batched_boards.get() + boards.len());
+            boards.iter().map(simple_heuristic).collect()
+        }
+
+        fn is_batched(&self) -> bool {
+            true
+        }
+    }
+
+    fn score_with_parameters(
+        board: &Board,
+        transposition_table: &TranspositionTable,
+        depth: i8,
+        parameters: SearchParameters,
+    ) -> i64 {
+        score_with_heuristic(
+            board,
+            transposition_table,
             depth,
-            &cancel_search,
+            parameters,
+            &simple_heuristic,
+        )
+    }
+
+    fn score_with_heuristic(
+        board: &Board,
+        transposition_table: &TranspositionTable,
+        depth: i8,
+        parameters: SearchParameters,
+        heuristic: &impl Heuristic,
+    ) -> i64 {
+        let (stop_condition, iteration_done) = (
+            StopCondition::new(SearchLimits::default()),
+            AtomicBool::new(false),
+        );
+        let mut move_ordering = MoveOrdering::new();
+        let mut context = SearchContext::new(
+            board,
+            heuristic,
+            parameters,
+            transposition_table,
+            &stop_condition,
+            &iteration_done,
+            &mut move_ordering,
         );
+        alpha_beta_score(board, NEG_INFINITY, POS_INFINITY, depth, 0, &mut context)
     }
-    if depth == 0 || board.is_end_game() {
-        return heuristic_score(&board);
+
+    fn fresh_score(board: &Board) -> i64 {
+        score(board, &TranspositionTable::new(1))
+    }
+
+    fn kings_board() -> Board {
+        // White: kings on A1, C1 and E1. Black: king on H8
+        Board::from_hash((
+            true,
+            (1 << 0) | (1 << 1) | (1 << 2),
+            1 << 31,
+            (1 << 0) | (1 << 1) | (1 << 2) | (1 << 31),
+        ))
+    }
+
+    #[test]
+    fn moves_without_capture_are_not_shared() {
+        let board = kings_board();
+        let late_board = kings_board().with_moves_without_capture(70);
+        assert_ne!(fresh_score(&board), fresh_score(&late_board));
+
+        let transposition_table = TranspositionTable::new(1);
+        score(&board, &transposition_table);
+        assert_eq!(
+            score(&late_board, &transposition_table),
+            fresh_score(&late_board)
+        );
     }
-    let mut handle = Vec::new();
-    let board = Arc::new(board);
-    for m in board.possible_moves() {
-        let board = board.clone();
-        let heuristic_score = cfg_select! {
-            nn_is_sync => heuristic_score.clone(),
-            not(nn_is_sync) => heuristic_score.deref().to_owned(),
+
+    #[test]
+    fn network_moves_without_capture_are_not_shared() {
+        // The network sees every value of the counter, far from the 40-move draw
+        let heuristic =
+            neural_network_heuristic(&generate_neural_networks(&mut seeded_rng(0)).swap_remove(0));
+        let network_score = |board: &Board, transposition_table: &TranspositionTable| {
+            score_with_heuristic(board, transposition_table, DEPTH, PARAMETERS, &heuristic)
         };
-        let cancel_search = cancel_search.clone();
-        handle.push(thread::spawn(move || {
-            let mut cloned_board = (*board).clone();
-            cloned_board.play(&m);
-            -threaded_score(
-                cloned_board,
-                cfg_select! {
-                    nn_is_sync => heuristic_score,
-                    not(nn_is_sync) => Arc::new(heuristic_score)
+        let board = kings_board();
+        let later_board = kings_board().with_moves_without_capture(10);
+        let fresh_later_score = network_score(&later_board, &TranspositionTable::new(1));
+        assert_ne!(
+            network_score(&board, &TranspositionTable::new(1)),
+            fresh_later_score
+        );
+
+        let transposition_table = TranspositionTable::new(1);
+        network_score(&board, &transposition_table);
+        assert_eq!(
+            network_score(&later_board, &transposition_table),
+            fresh_later_score
+        );
+    }
+
+    /// In `kings_board`, the kings of A1 and H8 go back and forth
+    fn shuffle_moves() -> impl Iterator<Item = Move> {
+        [
+            vec![(0, 0), (1, 1)],
+            vec![(7, 7), (6, 6)],
+            vec![(1, 1), (0, 0)],
+            vec![(6, 6), (7, 7)],
+        ]
+        .into_iter()
+        .cycle()
+    }
+
+    #[test]
+    fn repetitions_are_not_shared() {
+        // Black can draw by going back to H8 for the third time
+        let mut repeated_board = kings_board();
+        for m in shuffle_moves().take(7) {
+            repeated_board.play(&m);
+        }
+        assert_eq!(repeated_board.get_board_count(), 2);
+        let not_repeated_board =
+            Board::from_hash(repeated_board.hash()).with_moves_without_capture(7);
+        assert_eq!(fresh_score(&repeated_board), DRAW);
+        assert_ne!(fresh_score(&not_repeated_board), DRAW);
+
+        let transposition_table = TranspositionTable::new(1);
+        score(&not_repeated_board, &transposition_table);
+        assert_eq!(score(&repeated_board, &transposition_table), DRAW);
+    }
+
+    #[test]
+    fn move_orders_share_keys() {
+        let board = kings_board();
+        let transposed_board = |white_moves: [Move; 2]| {
+            let mut board = board.clone();
+            board.play(&white_moves[0]);
+            board.play(&vec![(7, 7), (6, 6)]);
+            board.play(&white_moves[1]);
+            board
+        };
+        let (a1_b2, c1_d2) = (vec![(0, 0), (1, 1)], vec![(2, 0), (3, 1)]);
+        assert_eq!(
+            transposed_board([a1_b2.clone(), c1_d2.clone()]).state_hash(),
+            transposed_board([c1_d2, a1_b2]).state_hash()
+        );
+    }
+
+    #[test]
+    fn earlier_repetitions_are_not_stored() {
+        // Black can draw by going back to H8 for the third time
+        let mut repeated_board = kings_board();
+        for m in shuffle_moves().take(7) {
+            repeated_board.play(&m);
+        }
+        // The same board with the same counters, reached twice too, but H8 was only reached once: black cannot draw
+        let mut transposed_board =
+            Board::from_hash(repeated_board.hash()).with_moves_without_capture(3);
+        for m in shuffle_moves().skip(3).take(4) {
+            transposed_board.play(&m);
+        }
+        assert_eq!(transposed_board.state_hash(), repeated_board.state_hash());
+        assert_eq!(fresh_score(&repeated_board), DRAW);
+        assert_ne!(fresh_score(&transposed_board), DRAW);
+
+        let transposition_table = TranspositionTable::new(1);
+        score(&repeated_board, &transposition_table);
+        assert_eq!(
+            score(&transposed_board, &transposition_table),
+            fresh_score(&transposed_board)
+        );
+        let transposition_table = TranspositionTable::new(1);
+        score(&transposed_board, &transposition_table);
+        assert_eq!(score(&repeated_board, &transposition_table), DRAW);
+    }
+
+    #[test]
+    fn draws_are_scored_with_contempt() {
+        let contempt_score = |board: &Board| {
+            score_with_parameters(
+                board,
+                &TranspositionTable::new(1),
+                DEPTH,
+                SearchParameters {
+                    contempt: CONTEMPT,
+                    ..PARAMETERS
                 },
-                depth - 1,
-                threads_depth - 1,
-                cancel_search,
             )
-        }));
-    }
-    let mut best_score = NEG_INFINITY;
-    for h in handle {
-        let score = h.join().unwrap();
-        best_score = i64::max(best_score, score);
+        };
+        let drawn_board = kings_board().with_moves_without_capture(80);
+        assert_eq!(fresh_score(&drawn_board), DRAW);
+        assert_eq!(contempt_score(&drawn_board), DRAW - CONTEMPT);
+
+        // White is ahead: close to the 40-move draw, it is worth less with contempt
+        let late_board = kings_board().with_moves_without_capture(70);
+        assert!(fresh_score(&late_board) > DRAW);
+        assert!(contempt_score(&late_board) < fresh_score(&late_board));
     }
 
-    best_score
-}
+    #[test]
+    fn contempt_scores_are_not_shared() {
+        // Every line is drawn by the 40-move rule
+        let board = kings_board().with_moves_without_capture(77);
+        let transposition_table = TranspositionTable::new(1);
+        let contempt_parameters = SearchParameters {
+            contempt: CONTEMPT,
+            ..PARAMETERS
+        };
+        let score = score_with_parameters(&board, &transposition_table, DEPTH, contempt_parameters);
+        assert_eq!(score, DRAW - CONTEMPT);
 
-fn threaded_best_moves(
-    board: Board,
-    #[cfg(nn_is_sync)] heuristic_score: Arc<impl Fn(&Board) -> i64 + Send + Sync + 'static>,
-    #[cfg(not(nn_is_sync))] heuristic_score: Arc<impl Fn(&Board) -> i64 + Send + Clone + 'static>,
-    depth: i8,
-    threads_depth: i8,
-    cancel_search: Arc<AtomicBool>,
-) -> Option<Vec<Move>> {
-    if cancel_search.load(Ordering::Acquire) {
-        return None;
+        // An analysis without contempt, after the search with contempt (cf `print_move_analysis`)
+        let (lines, _) = multi_pv_moves_list(
+            &board,
+            &simple_heuristic,
+            PARAMETERS,
+            DEPTH,
+            1,
+            &[],
+            &StopCondition::new(SearchLimits::default()),
+            &transposition_table,
+            &mut MoveOrdering::new(),
+        );
+        assert_eq!(lines.unwrap()[0].1, DRAW);
+        // The same contempt from the other player's side
+        let mut black_board = board.clone();
+        black_board.play(&board.possible_moves()[0]);
+        let score = score_with_parameters(
+            &black_board,
+            &transposition_table,
+            DEPTH,
+            contempt_parameters,
+        );
+        assert_eq!(score, DRAW - CONTEMPT);
     }
 
-    if depth == 0 || board.is_end_game() {
-        return Some(Vec::new());
+    #[test]
+    fn wins_are_scored_by_distance() {
+        // White: king on A1. Black: king on C3, which goes to B2, where white captures it
+        let board = Board::from_hash((false, 1 << 0, 1 << 9, (1 << 0) | (1 << 9)));
+        let mut lost_board = board.clone();
+        lost_board.play(&vec![(2, 2), (1, 1)]);
+        assert_eq!(fresh_score(&lost_board), win_score(1));
+
+        // The cached score of `lost_board` is counted from `lost_board`, not from `board`
+        let transposition_table = TranspositionTable::new(1);
+        score_at_depth(&board, &transposition_table, DEPTH + 1);
+        assert_eq!(score(&lost_board, &transposition_table), win_score(1));
     }
 
-    let mut handle = Vec::new();
-    let board = Arc::new(board);
-    for m in board.possible_moves() {
-        let board = board.clone();
-        let heuristic_score = cfg_select! {
-            nn_is_sync => heuristic_score.clone(),
-            not(nn_is_sync) => heuristic_score.deref().to_owned(),
+    #[test]
+    fn extensions_find_tactics() {
+        let without_extensions = SearchParameters {
+            max_extensions: 0,
+            ..PARAMETERS
         };
-        let cancel_search = cancel_search.clone();
-        handle.push(thread::spawn(move || {
-            let mut cloned_board = (*board).clone();
-            cloned_board.play(&m);
-            let res = -threaded_score(
-                cloned_board,
-                cfg_select! {
-                    nn_is_sync => heuristic_score,
-                    not(nn_is_sync) => Arc::new(heuristic_score)
-                },
-                depth - 1,
-                threads_depth - 1,
-                cancel_search,
+        for (fen, winning_move) in TACTICAL_POSITIONS {
+            assert!(solves_tactic(fen, winning_move, PARAMETERS), "{}", fen);
+            assert!(
+                !solves_tactic(fen, winning_move, without_extensions),
+                "{}",
+                fen
             );
-            (res, m)
-        }));
+        }
     }
-    let mut best_score = NEG_INFINITY;
-    let mut best_moves = Vec::new();
-    for h in handle {
-        let (score, m) = h.join().unwrap();
-        if score > best_score {
-            best_score = score;
-            best_moves = vec![m];
-        } else if score == best_score {
-            best_moves.push(m);
+
+    #[test]
+    fn promotions_do_not_extend() {
+        // Black: men on B2 and F4. White: men on D2 and E3, which can capture F4
+        let board = Board::from_hash((false, (1 << 5) | (1 << 10), (1 << 4) | (1 << 14), 0));
+        let promotion = vec![(1, 1), (0, 0)];
+        let mut child_board = board.clone();
+        child_board.play(&promotion);
+        assert_eq!(child_board.get_moves_without_capture(), 0);
+        assert!(child_board.can_jump());
+
+        let (stop_condition, iteration_done) = (
+            StopCondition::new(SearchLimits::default()),
+            AtomicBool::new(false),
+        );
+        let transposition_table = TranspositionTable::new(1);
+        let mut move_ordering = MoveOrdering::new();
+        let context = SearchContext::new(
+            &board,
+            &simple_heuristic,
+            PARAMETERS,
+            &transposition_table,
+            &stop_condition,
+            &iteration_done,
+            &mut move_ordering,
+        );
+        let nb_moves = board.possible_moves().len();
+        assert!(nb_moves > 1);
+        assert_eq!(context.extension(nb_moves, &board, &child_board), 0);
+    }
+
+    #[test]
+    fn batched_leaves_keep_scores() {
+        let mut board = Board::new();
+        for depth in 1..=6 {
+            let heuristic = BatchedHeuristic::default();
+            let batched_score = score_with_heuristic(
+                &board,
+                &TranspositionTable::new(1),
+                depth,
+                PARAMETERS,
+                &heuristic,
+            );
+            assert_eq!(
+                batched_score,
+                score_at_depth(&board, &TranspositionTable::new(1), depth)
+            );
+            assert!(heuristic.nb_batched_boards.get() > 0);
+            board.play(&board.possible_moves()[0]);
         }
     }
 
-    if cancel_search.load(Ordering::Acquire) {
-        return None;
+    #[test]
+    fn quiescence_leaves_are_batched() {
+        // Every move leads to an exchange
+        let board = board_of_fen(TACTICAL_POSITIONS[2].0).unwrap();
+        for depth in 1..=4 {
+            let heuristic = BatchedHeuristic::default();
+            let batched_score = score_with_heuristic(
+                &board,
+                &TranspositionTable::new(1),
+                depth,
+                PARAMETERS,
+                &heuristic,
+            );
+            assert_eq!(
+                batched_score,
+                score_at_depth(&board, &TranspositionTable::new(1), depth)
+            );
+            assert_eq!(heuristic.nb_single_boards.get(), 0);
+        }
     }
 
-    Some(best_moves)
-}
+    #[test]
+    fn multi_pv_scores_are_exact() {
+        let board = kings_board();
+        let nb_lines = 3;
+        let (lines, _) = multi_pv_moves_list(
+            &board,
+            &simple_heuristic,
+            PARAMETERS,
+            DEPTH,
+            nb_lines,
+            &[],
+            &StopCondition::new(SearchLimits::default()),
+            &TranspositionTable::new(1),
+            &mut MoveOrdering::new(),
+        );
+        let lines = lines.unwrap();
+        assert_eq!(lines.len(), nb_lines);
+        assert!(lines.is_sorted_by(|(_, score1), (_, score2)| score1 >= score2));
 
-fn best_move_first(
-    board: &Board,
-    heuristic_score: &impl Fn(&Board) -> i64,
-    cache: &mut HashMap<(BoardHash, i8), (i64, i64)>,
-    depth: i8,
-    cancel_search: &AtomicBool,
-) -> Vec<Move> {
-    if depth < BEST_MOVE_FIRST_MIN_DEPTH {
-        return board.possible_moves();
+        let mut scores = board
+            .possible_moves()
+            .into_iter()
+            .map(|m| {
+                let mut child_board = board.clone();
+                child_board.play(&m);
+                -score_at_depth(&child_board, &TranspositionTable::new(1), DEPTH - 1)
+            })
+            .collect::<Vec<_>>();
+        scores.sort_by(|score1, score2| score2.cmp(score1));
+        for ((_, score), expected_score) in lines.iter().zip(scores) {
+            assert_eq!(*score, expected_score);
+        }
     }
-    let mut moves = board
-        .possible_moves()
-        .into_iter()
-        .map(|m| {
-            let mut cloned_board = board.clone();
-            cloned_board.play(&m);
-            let sign = if BEST_MOVE_FIRST_SKIP_SIZE % 2 == 0 {
-                1
-            } else {
-                -1
-            };
-            let score = alpha_beta_score(
-                &cloned_board,
-                NEG_INFINITY,
-                POS_INFINITY,
-                heuristic_score,
-                cache,
-                depth - BEST_MOVE_FIRST_SKIP_SIZE,
-                cancel_search,
+
+    #[test]
+    fn principal_variation_stops_at_illegal_move() {
+        let board = Board::new();
+        let first_move = board.possible_moves().swap_remove(0);
+        let mut child_board = board.clone();
+        child_board.play(&first_move);
+        let transposition_table = TranspositionTable::new(1);
+        // As if the entry of another position with the same key
+        transposition_table.insert(
+            child_board.state_hash(),
+            TranspositionEntry {
+                score: 0,
+                depth: DEPTH,
+                bound: Bound::Exact,
+                best_move: Some(child_board.possible_moves().len()),
+            },
+        );
+        let principal_variation = principal_variation(
+            &board,
+            &first_move,
+            PARAMETERS,
+            &transposition_table,
+            DEPTH,
+            0,
+        );
+        assert_eq!(principal_variation.moves, vec![first_move]);
+    }
+
+    fn best_moves_with_root_alpha(
+        board: &Board,
+        window: (i64, i64),
+        root_alpha: &AtomicI64,
+        first_move: usize,
+    ) -> (Vec<Move>, i64) {
+        let (stop_condition, iteration_done) = (
+            StopCondition::new(SearchLimits::default()),
+            AtomicBool::new(false),
+        );
+        let transposition_table = TranspositionTable::new(1);
+        let mut move_ordering = MoveOrdering::new();
+        let mut context = SearchContext::new(
+            board,
+            &simple_heuristic,
+            PARAMETERS,
+            &transposition_table,
+            &stop_condition,
+            &iteration_done,
+            &mut move_ordering,
+        );
+        alpha_beta_best_moves(board, DEPTH, window, root_alpha, first_move, &mut context).unwrap()
+    }
+
+    #[test]
+    fn shared_root_alpha_keeps_best_moves() {
+        let board = kings_board();
+        let window = (NEG_INFINITY, POS_INFINITY);
+        let (best_moves, best_score) =
+            best_moves_with_root_alpha(&board, window, &AtomicI64::new(NEG_INFINITY), 0);
+        // As if another thread had found the best score first: the other moves fail low and are ignored
+        for first_move in 0..board.possible_moves().len() {
+            let (shared_best_moves, shared_best_score) =
+                best_moves_with_root_alpha(&board, window, &AtomicI64::new(best_score), first_move);
+            assert_eq!(shared_best_score, best_score);
+            assert_eq!(shared_best_moves.len(), best_moves.len());
+            assert!(shared_best_moves.iter().all(|m| best_moves.contains(m)));
+        }
+    }
+
+    #[test]
+    fn only_exact_scores_are_shared() {
+        let board = kings_board();
+        let (_, best_score) = best_moves_with_root_alpha(
+            &board,
+            (NEG_INFINITY, POS_INFINITY),
+            &AtomicI64::new(NEG_INFINITY),
+            0,
+        );
+        let beta = best_score - 1;
+        let root_alpha = AtomicI64::new(NEG_INFINITY);
+        let (_, score) = best_moves_with_root_alpha(&board, (NEG_INFINITY, beta), &root_alpha, 0);
+        assert!(score >= beta);
+        assert!(root_alpha.load(Ordering::Relaxed) < beta);
+    }
+
+    /// Nodes of an iterative deepening up to `max_depth`, the move orderings being kept between the iterations or not
+    fn iterative_deepening_nodes(board: &Board, max_depth: i8, keep_move_orderings: bool) -> u64 {
+        let transposition_table = TranspositionTable::new(16);
+        let mut move_orderings = Vec::new();
+        let mut nodes = 0;
+        for depth in (2..=max_depth).step_by(2) {
+            if !keep_move_orderings {
+                move_orderings.clear();
+            }
+            let (best_moves, counters) = alpha_beta_moves_list(
+                board,
+                &Arc::new(simple_heuristic),
+                PARAMETERS,
+                depth,
+                (NEG_INFINITY, POS_INFINITY),
+                false,
+                &StopCondition::new(SearchLimits::default()),
+                &transposition_table,
+                &mut move_orderings,
             );
-            let res = sign * score;
-            (m, res)
-        })
-        .collect::<Vec<_>>();
-    moves.sort_by_key(|m| Reverse(m.1));
-    moves.into_iter().map(|(m, _)| m).collect::<Vec<_>>()
+            assert!(best_moves.is_some());
+            nodes += counters.nodes;
+        }
+        nodes
+    }
+
+    #[test]
+    fn kept_move_orderings_reduce_nodes() {
+        let board = board_of_fen(TACTICAL_POSITIONS[2].0).unwrap();
+        let kept_nodes = iterative_deepening_nodes(&board, 8, true);
+        let reset_nodes = iterative_deepening_nodes(&board, 8, false);
+        assert!(kept_nodes < reset_nodes);
+    }
 }
@@ -0,0 +1,60 @@
+use crate::checkers::board::Board;
+
+/// Score scale of the heuristics which do not set theirs: a man for `simple_heuristic` (cf `Heuristic::score_scale`)
+pub const DEFAULT_SCORE_SCALE: f64 = 1_000_000.;
+
+/// Score of a board, from the current player POV. Every `Fn(&Board) -> i64` is a heuristic.
+/// A heuristic can also score several boards at once, e.g. with a single matrix-matrix product (cf `scores`),
+/// or keep a state along the explored line, updated at each move instead of being recomputed for each board (cf `State`)
+pub trait Heuristic {
+    /// E.g. the first layer of a neural network (cf `Accumulator`). `()` for stateless heuristics
+    type State;
+
+    fn score(&self, board: &Board) -> i64;
+
+    /// State of the root of a search
+    fn state(&self, board: &Board) -> Self::State;
+
+    /// State of `child_board`, reached by a move from `board`.
+    /// Undoing the move only drops this state: the state of `board` is kept by the search
+    fn play(&self, state: &Self::State, board: &Board, child_board: &Board) -> Self::State;
+
+    /// Same as `score`, `state` being the state of `board`
+    fn score_with_state(&self, board: &Board, _state: &Self::State) -> i64 {
+        self.score(board)
+    }
+
+    /// By default, each board is scored on its own
+    fn scores(&self, boards: &[Board]) -> Vec<i64> {
+        boards.iter().map(|board| self.score(board)).collect()
+    }
+
+    /// Score which multiplies the odds of winning by e, around equal positions.
+    /// It is the unit of the scores, e.g. of the noise of the strength levels (cf `StrengthLevel::eval_noise`)
+    fn score_scale(&self) -> f64 {
+        DEFAULT_SCORE_SCALE
+    }
+
+    /// Probability of winning of the current player, e.g. for the MCTS leaves (cf `Playout::Heuristic`).
+    /// By default, the logistic function of the score in `score_scale` units
+    fn win_probability(&self, score: i64) -> f64 {
+        1. / (1. + (-(score as f64) / self.score_scale()).exp())
+    }
+
+    /// If `true`, the search scores the leaves of a node together with `scores`, before knowing which ones get pruned
+    fn is_batched(&self) -> bool {
+        false
+    }
+}
+
+impl<F: Fn(&Board) -> i64> Heuristic for F {
+    type State = ();
+
+    fn score(&self, board: &Board) -> i64 {
+        self(board)
+    }
+
+    fn state(&self, _board: &Board) {}
+
+    fn play(&self, _state: &(), _board: &Board, _child_board: &Board) {}
+}
@@ -0,0 +1,84 @@
+use crate::checkers::board::{BOARD_SIZE, Board, Move};
+use std::cmp::Reverse;
+
+// Move ordering: the transposition table best move first, then the killer moves, then the moves sorted by history.
+// Killer moves are the last moves which caused a beta cutoff at the same ply, in a sibling node.
+// The history table scores each move (by player, starting square and arrival square) with the cutoffs it caused.
+
+const NB_KILLER_MOVES: usize = 2;
+const NB_SQUARES: usize = (BOARD_SIZE * BOARD_SIZE) as usize;
+
+/// Priority of the transposition table best move, above every killer move
+const TRANSPOSITION_PRIORITY: u64 = u64::MAX;
+/// Priority of the first killer move, above every history score
+const KILLER_PRIORITY: u64 = u64::MAX / 2;
+
+pub struct MoveOrdering {
+    killer_moves: Vec<[Option<Move>; NB_KILLER_MOVES]>,
+    history: Vec<u64>,
+}
+
+impl MoveOrdering {
+    pub fn new() -> Self {
+        MoveOrdering {
+            killer_moves: Vec::new(),
+            history: vec![0; 2 * NB_SQUARES * NB_SQUARES],
+        }
+    }
+
+    /// Possible moves with their index in `Board::possible_moves`, best ones first
+    pub fn order_moves(
+        &self,
+        board: &Board,
+        ply: usize,
+        transposition_best_move: Option<usize>,
+    ) -> Vec<(usize, Move)> {
+        let mut moves = board
+            .possible_moves()
+            .into_iter()
+            .enumerate()
+            .collect::<Vec<_>>();
+        let killer_moves = self.killer_moves.get(ply);
+        // Stable sort: moves with the same priority keep the `possible_moves` order
+        moves.sort_by_cached_key(|(index, m)| {
+            if transposition_best_move == Some(*index) {
+                return Reverse(TRANSPOSITION_PRIORITY);
+            }
+            if let Some(killer_moves) = killer_moves
+                && let Some(position) = killer_moves
+                    .iter()
+                    .position(|killer_move| killer_move.as_ref() == Some(m))
+            {
+                return Reverse(KILLER_PRIORITY - position as u64);
+            }
+            Reverse(self.history[history_index(board, m)])
+        });
+        moves
+    }
+
+    /// Called when `m` causes a beta cutoff
+    pub fn add_cutoff(&mut self, board: &Board, ply: usize, m: &Move, depth: i8) {
+        if self.killer_moves.len() <= ply {
+            self.killer_moves
+                .resize(ply + 1, [const { None }; NB_KILLER_MOVES]);
+        }
+        let killer_moves = &mut self.killer_moves[ply];
+        if killer_moves[0].as_ref() != Some(m) {
+            killer_moves.rotate_right(1);
+            killer_moves[0] = Some(m.clone());
+        }
+        // Deep cutoffs are more reliable than shallow ones
+        self.history[history_index(board, m)] += (depth as u64) * (depth as u64);
+    }
+}
+
+fn history_index(board: &Board, m: &Move) -> usize {
+    let player = usize::from(!board.get_player_is_white());
+    let from = square_index(m[0]);
+    let to = square_index(*m.last().unwrap());
+    (player * NB_SQUARES + from) * NB_SQUARES + to
+}
+
+fn square_index((x, y): (i8, i8)) -> usize {
+    (y * BOARD_SIZE + x) as usize
+}
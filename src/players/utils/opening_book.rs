@@ -0,0 +1,357 @@
+use crate::checkers::board::{Board, BoardHash, Move};
+use crate::checkers::pdn::{
+    PdnGame, board_of_fen, fen_of_board, move_of_pdn, pdn_games, pdn_of_move,
+};
+use crate::checkers::player::Player::{Black, White};
+use crate::checkers::win_status::WinStatus;
+use crate::checkers::win_status::WinStatus::{Continue, Draw, Win};
+use crate::consts::{
+    ANALYSIS_SEARCH_PARAMETERS, BOOK_FILE, BOOK_PLIES, BOOK_SEARCH_MARGIN, BOOK_SEARCH_PLIES,
+    BOOK_SEARCH_TIME, BOOK_SEARCH_WIDTH, TRANSPOSITION_TABLE_SIZE,
+};
+use crate::players::alpha_beta::get_move::simple_heuristic;
+use crate::players::alpha_beta::multi_pv::multi_pv;
+use crate::players::utils::alpha_beta::{SearchLimits, SearchResult};
+use crate::players::utils::time_control::TimeBudget;
+use crate::players::utils::transposition_table::TranspositionTable;
+use crate::random::Rng;
+use rand::RngExt;
+use std::collections::HashMap;
+use std::fs;
+use std::sync::OnceLock;
+use std::time::Instant;
+
+// The opening book stores, for each position of the openings (keyed by `Board::hash`), the moves played from it,
+// each one with a weight and the results of the games where it was played.
+// It is built from PDN games (their first BOOK_PLIES plies), or by expanding the best moves found by searches.
+// The book file has one line per move: `<FEN> <move> <weight> <wins> <draws> <losses>` (cf `fen_of_board` and `pdn_of_move`).
+
+static BOOK: OnceLock<Option<OpeningBook>> = OnceLock::new();
+
+/// How a move is chosen among the book moves of a position
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BookChoice {
+    /// The book is not probed
+    Disabled,
+    /// The move with the highest weight
+    Best,
+    /// A random move, with a probability proportional to its weight, so that games do not all follow the same opening
+    Weighted,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct BookMove {
+    pub m: Move,
+    pub weight: u32,
+    /// Results of the games where the move was played, from the POV of the player of the move
+    pub wins: u32,
+    pub draws: u32,
+    pub losses: u32,
+}
+
+#[derive(Default)]
+pub struct OpeningBook {
+    positions: HashMap<BoardHash, Vec<BookMove>>,
+}
+
+impl OpeningBook {
+    pub fn new() -> OpeningBook {
+        OpeningBook::default()
+    }
+
+    pub fn get(&self, board: &Board) -> Option<&[BookMove]> {
+        self.positions.get(&board.hash()).map(Vec::as_slice)
+    }
+
+    pub fn get_nb_positions(&self) -> usize {
+        self.positions.len()
+    }
+
+    /// Adds `weight` to the move `m` of `board`, and the result of its game if it is known
+    pub fn add(&mut self, board: &Board, m: &Move, weight: u32, result: Option<WinStatus>) {
+        let book_moves = self.positions.entry(board.hash()).or_default();
+        if !book_moves.iter().any(|book_move| &book_move.m == m) {
+            book_moves.push(BookMove {
+                m: m.clone(),
+                weight: 0,
+                wins: 0,
+                draws: 0,
+                losses: 0,
+            });
+        }
+        let book_move = book_moves
+            .iter_mut()
+            .find(|book_move| &book_move.m == m)
+            .unwrap();
+        book_move.weight += weight;
+        match result {
+            Some(Win(player)) if player.is_white() == board.get_player_is_white() => {
+                book_move.wins += 1;
+            }
+            Some(Win(_)) => book_move.losses += 1,
+            Some(Draw) => book_move.draws += 1,
+            Some(Continue) | None => (),
+        }
+    }
+
+    /// Adds the first `BOOK_PLIES` moves of `game`, each one with a weight of 1
+    pub fn add_game(&mut self, game: &PdnGame) {
+        // PDN colors are swapped (cf `src/checkers/pdn.rs`)
+        let result = match game.result.as_str() {
+            "2-0" | "1-0" => Some(Win(Black)),
+            "0-2" | "0-1" => Some(Win(White)),
+            "1-1" | "1/2-1/2" => Some(Draw),
+            _ => None,
+        };
+        let mut board = game.board.clone();
+        for m in game.moves.iter().take(BOOK_PLIES) {
+            self.add(&board, m, 1, result);
+            board.play(m);
+        }
+    }
+
+    /// `None` if the book has no legal move for `board`
+    pub fn choose_move(&self, board: &Board, choice: BookChoice, rng: &mut Rng) -> Option<Move> {
+        let possible_moves = board.possible_moves();
+        let book_moves = self
+            .get(board)?
+            .iter()
+            .filter(|book_move| book_move.weight > 0 && possible_moves.contains(&book_move.m))
+            .collect::<Vec<_>>();
+        match choice {
+            BookChoice::Disabled => None,
+            BookChoice::Best => book_moves
+                .into_iter()
+                .max_by_key(|book_move| book_move.weight)
+                .map(|book_move| book_move.m.clone()),
+            BookChoice::Weighted => {
+                let total_weight = book_moves
+                    .iter()
+                    .map(|book_move| book_move.weight)
+                    .sum::<u32>();
+                if total_weight == 0 {
+                    return None;
+                }
+                let mut target = rng.random_range(0..total_weight);
+                for book_move in book_moves {
+                    if target < book_move.weight {
+                        return Some(book_move.m.clone());
+                    }
+                    target -= book_move.weight;
+                }
+                unreachable!()
+            }
+        }
+    }
+
+    pub fn to_text(&self) -> String {
+        let mut lines = Vec::new();
+        for (&hash, book_moves) in &self.positions {
+            let board = Board::from_hash(hash);
+            for book_move in book_moves {
+                lines.push(format!(
+                    "{} {} {} {} {} {}",
+                    fen_of_board(&board),
+                    pdn_of_move(&book_move.m),
+                    book_move.weight,
+                    book_move.wins,
+                    book_move.draws,
+                    book_move.losses
+                ));
+            }
+        }
+        lines.sort();
+        lines.push(String::new());
+        lines.join("\n")
+    }
+
+    /// Inverse of `to_text`. `None` if a line is invalid
+    pub fn of_text(text: &str) -> Option<OpeningBook> {
+        let mut book = OpeningBook::new();
+        for line in text.lines().filter(|line| !line.trim().is_empty()) {
+            let words = line.split_whitespace().collect::<Vec<_>>();
+            let [fen, m, weight, wins, draws, losses] = words[..] else {
+                return None;
+            };
+            let board = board_of_fen(fen)?;
+            let m = move_of_pdn(&board, m)?;
+            let book_moves = book.positions.entry(board.hash()).or_default();
+            book_moves.push(BookMove {
+                m,
+                weight: weight.parse().ok()?,
+                wins: wins.parse().ok()?,
+                draws: draws.parse().ok()?,
+                losses: losses.parse().ok()?,
+            });
+        }
+        Some(book)
+    }
+}
+
+/// The move chosen by `choice` among the moves of the book file (cf `BOOK_FILE`), if any.
+/// The book is loaded on the first call; without book file, no move is found
+pub fn probe_book(board: &Board, choice: BookChoice, rng: &mut Rng) -> Option<Move> {
+    if choice == BookChoice::Disabled {
+        return None;
+    }
+    BOOK.get_or_init(|| load_book(BOOK_FILE))
+        .as_ref()?
+        .choose_move(board, choice, rng)
+}
+
+/// Search result of a book move (cf `probe_book`), without principal variation
+pub fn book_search_result(
+    board: &Board,
+    choice: BookChoice,
+    rng: &mut Rng,
+) -> Option<SearchResult> {
+    Some(SearchResult {
+        best_move: probe_book(board, choice, rng)?,
+        principal_variations: Vec::new(),
+        nodes: 0,
+    })
+}
+
+/// Builds the book from the games of `pdn_files`, or by searches if there is none (cf `build_book_from_search`)
+pub fn run_book(pdn_files: &[String]) {
+    let t0 = Instant::now();
+    let book = if pdn_files.is_empty() {
+        build_book_from_search()
+    } else {
+        build_book_from_pdn(pdn_files)
+    };
+    println!("{} positions, {:?}", book.get_nb_positions(), t0.elapsed());
+    fs::write(BOOK_FILE, book.to_text()).unwrap();
+    println!("Book saved in: {}", BOOK_FILE);
+}
+
+pub fn build_book_from_pdn(pdn_files: &[String]) -> OpeningBook {
+    let mut book = OpeningBook::new();
+    for file in pdn_files {
+        let games = pdn_games(&fs::read_to_string(file).unwrap());
+        println!("{}: {} games", file, games.len());
+        for game in &games {
+            book.add_game(game);
+        }
+    }
+    book
+}
+
+/// From the initial board, adds the `BOOK_SEARCH_WIDTH` best moves of each position (within `BOOK_SEARCH_MARGIN` of the best one),
+/// up to `BOOK_SEARCH_PLIES` plies. The better a move is ranked, the higher its weight
+pub fn build_book_from_search() -> OpeningBook {
+    let mut book = OpeningBook::new();
+    let transposition_table = TranspositionTable::new(TRANSPOSITION_TABLE_SIZE);
+    expand_book(
+        &mut book,
+        &Board::new(),
+        BOOK_SEARCH_PLIES,
+        &transposition_table,
+    );
+    book
+}
+
+fn expand_book(
+    book: &mut OpeningBook,
+    board: &Board,
+    plies: usize,
+    transposition_table: &TranspositionTable,
+) {
+    if plies == 0 || board.is_end_game() || book.get(board).is_some() {
+        return;
+    }
+    let lines = multi_pv(
+        board,
+        &simple_heuristic,
+        ANALYSIS_SEARCH_PARAMETERS,
+        BOOK_SEARCH_WIDTH,
+        SearchLimits {
+            time: Some(TimeBudget::fixed(BOOK_SEARCH_TIME)),
+            ..SearchLimits::default()
+        },
+        transposition_table,
+    );
+    let Some(best_score) = lines.first().map(|line| line.score) else {
+        return;
+    };
+    println!("{}: {}", fen_of_board(board), lines[0]);
+    for (rank, line) in lines.iter().enumerate() {
+        if best_score - line.score > BOOK_SEARCH_MARGIN {
+            break;
+        }
+        book.add(
+            board,
+            &line.moves[0],
+            (BOOK_SEARCH_WIDTH - rank) as u32,
+            None,
+        );
+    }
+    for book_move in book.get(board).unwrap().to_vec() {
+        let mut cloned_board = board.clone();
+        cloned_board.play(&book_move.m);
+        expand_book(book, &cloned_board, plies - 1, transposition_table);
+    }
+}
+
+fn load_book(file: &str) -> Option<OpeningBook> {
+    let text = fs::read_to_string(file).ok()?;
+    println!("Loads: {}", file);
+    Some(OpeningBook::of_text(&text).unwrap_or_else(|| panic!("Invalid book: {}", file)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BookChoice, OpeningBook};
+    use crate::checkers::board::Board;
+    use crate::checkers::pdn::{move_of_pdn, pdn_games};
+    use crate::random::seeded_rng;
+
+    const GAMES: &str = "[Event \"1\"]\n[Result \"2-0\"]\n\n1. 11-15 23-19 2. 8-11 {comment} 22-17 (2... 22-18) 2-0\n\
+                         [Event \"2\"]\n[Result \"1-1\"]\n\n1. 11-15 24-20 2. 15-18 1-1\n\
+                         [Event \"3\"]\n[Result \"0-2\"]\n\n1. 9-13 22-18 0-2\n";
+
+    fn book() -> OpeningBook {
+        let mut book = OpeningBook::new();
+        for game in pdn_games(GAMES) {
+            book.add_game(&game);
+        }
+        book
+    }
+
+    #[test]
+    fn book_moves_are_weighted_by_games() {
+        let book = book();
+        let board = Board::new();
+        let book_moves = book.get(&board).unwrap();
+        assert_eq!(book_moves.len(), 2);
+        let first_move = &book_moves[0];
+        assert_eq!(first_move.m, move_of_pdn(&board, "11-15").unwrap());
+        // PDN's White (the second player) wins the first game
+        assert_eq!(
+            (
+                first_move.weight,
+                first_move.wins,
+                first_move.draws,
+                first_move.losses
+            ),
+            (2, 0, 1, 1)
+        );
+        assert_eq!(
+            book.choose_move(&board, BookChoice::Best, &mut seeded_rng(0)),
+            Some(first_move.m.clone())
+        );
+        assert_eq!(
+            book.choose_move(&board, BookChoice::Disabled, &mut seeded_rng(0)),
+            None
+        );
+    }
+
+    #[test]
+    fn book_text_is_reversible() {
+        let book = book();
+        let text = book.to_text();
+        let loaded_book = OpeningBook::of_text(&text).unwrap();
+        assert_eq!(loaded_book.get_nb_positions(), book.get_nb_positions());
+        assert_eq!(loaded_book.to_text(), text);
+    }
+}
@@ -0,0 +1,311 @@
+use crate::checkers::bitboard::BitBoard;
+use crate::checkers::board::{BOARD_SIZE, Board, BoardHash, MAX_MOVES_WITHOUT_CAPTURE, Move};
+use crate::checkers::win_status::WinStatus::Draw;
+use crate::consts::TABLEBASE_FILE;
+use crate::players::alpha_beta::score::{DRAW, NEG_INFINITY, win_score};
+use crate::players::utils::alpha_beta::PrincipalVariation;
+use TablebaseResult::{Loss, Win};
+use std::collections::HashMap;
+use std::fs;
+use std::sync::OnceLock;
+
+// Every position with at most MAX_TABLEBASE_PIECES pieces is solved by retrograde analysis.
+// Each position is stored with its distance to the end of the game (in plies), assuming perfect play:
+// an odd distance is a win for the current player, an even distance is a loss, and DRAW_DISTANCE is a draw.
+// The 40-move and repetition rules are ignored when solving, they are checked when probing.
+
+pub const MAX_TABLEBASE_PIECES: u32 = 3;
+const DRAW_DISTANCE: i16 = -1;
+const NB_SQUARES: usize = (BOARD_SIZE * BOARD_SIZE / 2) as usize;
+
+static TABLEBASE: OnceLock<Option<HashMap<BoardHash, i16>>> = OnceLock::new();
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TablebaseResult {
+    Win(i16),
+    Loss(i16),
+    Draw,
+}
+
+impl TablebaseResult {
+    /// Current player POV
+    pub fn score(self) -> i64 {
+        match self {
+            Win(distance) => win_score(distance as i64),
+            Loss(distance) => -win_score(distance as i64),
+            TablebaseResult::Draw => DRAW,
+        }
+    }
+}
+
+pub fn probe_tablebase(board: &Board) -> Option<TablebaseResult> {
+    if board.get_nb_pieces() > MAX_TABLEBASE_PIECES {
+        return None;
+    }
+    if board.get_win_status() == Draw {
+        return Some(TablebaseResult::Draw);
+    }
+    let tablebase = TABLEBASE
+        .get_or_init(|| load_tablebase(TABLEBASE_FILE))
+        .as_ref()?;
+    probe(tablebase, board)
+}
+
+/// Same as `probe_tablebase`, in the given tablebase
+fn probe(tablebase: &HashMap<BoardHash, i16>, board: &Board) -> Option<TablebaseResult> {
+    let distance = *tablebase.get(&board.hash())?;
+    if distance == DRAW_DISTANCE {
+        return Some(TablebaseResult::Draw);
+    }
+    if distance >= (MAX_MOVES_WITHOUT_CAPTURE - board.get_moves_without_capture()) as i16 {
+        // The game may end in a draw before reaching the end, the result is unknown
+        return None;
+    }
+    if distance % 2 == 1 {
+        Some(Win(distance))
+    } else {
+        Some(Loss(distance))
+    }
+}
+
+/// Returns the fastest winning moves, or else the drawing moves, or else the longest-resisting losing moves
+pub fn tablebase_best_moves(board: &Board) -> Option<Vec<Move>> {
+    probe_tablebase(board)?;
+    let mut best_score = NEG_INFINITY;
+    let mut best_moves = Vec::new();
+    for m in board.possible_moves() {
+        let mut cloned_board = board.clone();
+        cloned_board.play(&m);
+        let score = -probe_tablebase(&cloned_board)?.score();
+        if score > best_score {
+            best_score = score;
+            best_moves = vec![m];
+        } else if score == best_score {
+            best_moves.push(m);
+        }
+    }
+    Some(best_moves)
+}
+
+pub fn tablebase_principal_variation(board: &Board, first_move: &Move) -> PrincipalVariation {
+    let score = probe_tablebase(board).unwrap().score();
+    let mut board = board.clone();
+    let mut moves = vec![first_move.clone()];
+    board.play(first_move);
+    while !board.is_end_game()
+        && let Some(best_moves) = tablebase_best_moves(&board)
+    {
+        board.play(&best_moves[0]);
+        moves.push(best_moves[0].clone());
+    }
+    PrincipalVariation {
+        depth: moves.len() as i8,
+        score,
+        moves,
+    }
+}
+
+pub fn generate_tablebase(file: &str) {
+    let positions = get_all_positions();
+    println!("{} positions", positions.len());
+    store_tablebase(&solve_positions(&positions), file);
+}
+
+/// Distance of each position. The moves of `positions` must lead to `positions` only
+fn solve_positions(positions: &[BoardHash]) -> Vec<i16> {
+    let indexes = positions
+        .iter()
+        .enumerate()
+        .map(|(index, &hash)| (hash, index as u32))
+        .collect::<HashMap<_, _>>();
+
+    // ********** Compute the children of every position **********
+    let mut children = Vec::new();
+    let mut children_offsets = vec![0];
+    for &hash in positions {
+        let board = Board::from_hash(hash);
+        for m in board.possible_moves() {
+            let mut cloned_board = board.clone();
+            cloned_board.play(&m);
+            children.push(indexes[&cloned_board.hash()]);
+        }
+        children_offsets.push(children.len());
+    }
+
+    // ********** Retrograde analysis **********
+    let mut distances = vec![DRAW_DISTANCE; positions.len()];
+    let mut distance = 0;
+    loop {
+        let mut nb_new_positions = 0;
+        for index in 0..positions.len() {
+            if distances[index] != DRAW_DISTANCE {
+                continue;
+            }
+            let mut children_distances = children
+                [children_offsets[index]..children_offsets[index + 1]]
+                .iter()
+                .map(|&child| distances[child as usize]);
+            let is_solved = if distance % 2 == 0 {
+                // Every move leads to a win of the opponent
+                children_distances.all(|d| d != DRAW_DISTANCE && d % 2 == 1)
+            } else {
+                // A move leads to a loss of the opponent
+                children_distances.any(|d| d == distance - 1)
+            };
+            if is_solved {
+                distances[index] = distance;
+                nb_new_positions += 1;
+            }
+        }
+        println!("Distance {distance}: {nb_new_positions} positions");
+        if nb_new_positions == 0 {
+            break;
+        }
+        distance += 1;
+    }
+    distances
+}
+
+fn get_all_positions() -> Vec<BoardHash> {
+    let mut positions = Vec::new();
+    for nb_pieces in 1..=MAX_TABLEBASE_PIECES {
+        add_positions(&mut positions, nb_pieces, 0, (0, 0, 0));
+    }
+    positions
+}
+
+fn add_positions(
+    positions: &mut Vec<BoardHash>,
+    nb_pieces: u32,
+    first_square: usize,
+    bitboards: (u32, u32, u32),
+) {
+    if nb_pieces == 0 {
+        let (white_bitboard, black_bitboard, king_bitboard) = bitboards;
+        positions.push((true, white_bitboard, black_bitboard, king_bitboard));
+        positions.push((false, white_bitboard, black_bitboard, king_bitboard));
+        return;
+    }
+    for square in first_square..NB_SQUARES {
+        let y = (square / (BOARD_SIZE / 2) as usize) as i8;
+        for (is_white, is_king) in [(true, false), (true, true), (false, false), (false, true)] {
+            if !is_king && y == if is_white { BOARD_SIZE - 1 } else { 0 } {
+                // A man on its promotion line
+                continue;
+            }
+            let (mut white_bitboard, mut black_bitboard, mut king_bitboard) = bitboards;
+            if is_white {
+                white_bitboard.set_bit(square, true);
+            } else {
+                black_bitboard.set_bit(square, true);
+            }
+            king_bitboard.set_bit(square, is_king);
+            add_positions(
+                positions,
+                nb_pieces - 1,
+                square + 1,
+                (white_bitboard, black_bitboard, king_bitboard),
+            );
+        }
+    }
+}
+
+fn store_tablebase(distances: &[i16], file: &str) {
+    let mut content = Vec::new();
+    content.extend(MAX_TABLEBASE_PIECES.to_le_bytes());
+    for distance in distances {
+        content.extend(distance.to_le_bytes());
+    }
+    fs::write(file, content).unwrap();
+    println!("Tablebase saved in: {}", file);
+}
+
+fn load_tablebase(file: &str) -> Option<HashMap<BoardHash, i16>> {
+    let content = fs::read(file).ok()?;
+    println!("Loads: {}", file);
+    let (header, content) = content.split_at(size_of::<u32>());
+    assert_eq!(
+        u32::from_le_bytes(header.try_into().unwrap()),
+        MAX_TABLEBASE_PIECES
+    );
+    let positions = get_all_positions();
+    assert_eq!(positions.len() * size_of::<i16>(), content.len());
+    let distances = content
+        .chunks_exact(size_of::<i16>())
+        .map(|bytes| i16::from_le_bytes(bytes.try_into().unwrap()));
+    Some(positions.into_iter().zip(distances).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{TablebaseResult, get_all_positions, probe, solve_positions};
+    use crate::checkers::board::{BoardHash, MAX_MOVES_WITHOUT_CAPTURE};
+    use crate::checkers::pdn::{board_of_fen, move_of_pdn};
+    use std::collections::HashMap;
+    use std::sync::OnceLock;
+
+    /// Generated once for all the tests, instead of loading `TABLEBASE_FILE`
+    fn tablebase() -> &'static HashMap<BoardHash, i16> {
+        static TABLEBASE: OnceLock<HashMap<BoardHash, i16>> = OnceLock::new();
+        TABLEBASE.get_or_init(|| {
+            let positions = get_all_positions();
+            let distances = solve_positions(&positions);
+            positions.into_iter().zip(distances).collect()
+        })
+    }
+
+    fn probe_fen(fen: &str) -> Option<TablebaseResult> {
+        probe(tablebase(), &board_of_fen(fen).unwrap())
+    }
+
+    #[test]
+    fn last_capture_wins() {
+        let mut board = board_of_fen("B:W22:B18").unwrap();
+        assert_eq!(probe(tablebase(), &board), Some(TablebaseResult::Win(1)));
+        board.play(&move_of_pdn(&board, "18x25").unwrap());
+        assert_eq!(probe(tablebase(), &board), Some(TablebaseResult::Loss(0)));
+    }
+
+    #[test]
+    fn two_kings_beat_one_king() {
+        let Some(TablebaseResult::Win(distance)) = probe_fen("W:WK1,K3:BK32") else {
+            panic!("Not a win");
+        };
+        assert!(distance > 1);
+        // Whichever side plays, the two kings win
+        assert!(matches!(
+            probe_fen("B:WK1,K3:BK32"),
+            Some(TablebaseResult::Loss(_))
+        ));
+        assert!(matches!(
+            probe_fen("B:WK29:BK4,K14"),
+            Some(TablebaseResult::Win(_))
+        ));
+    }
+
+    #[test]
+    fn one_king_draws_against_one_king() {
+        assert_eq!(probe_fen("W:WK1:BK32"), Some(TablebaseResult::Draw));
+        assert_eq!(probe_fen("B:WK5:BK28"), Some(TablebaseResult::Draw));
+    }
+
+    #[test]
+    fn win_beyond_the_40_move_draw_is_unknown() {
+        let board = board_of_fen("W:WK1,K3:BK32").unwrap();
+        let Some(TablebaseResult::Win(distance)) = probe(tablebase(), &board) else {
+            panic!("Not a win");
+        };
+        // The 40-move budget is one ply longer than the win
+        let late_board = board
+            .clone()
+            .with_moves_without_capture(MAX_MOVES_WITHOUT_CAPTURE - distance as i8 - 1);
+        assert_eq!(
+            probe(tablebase(), &late_board),
+            Some(TablebaseResult::Win(distance))
+        );
+        // The budget is as long as the win: the game may end in a draw first
+        let late_board =
+            board.with_moves_without_capture(MAX_MOVES_WITHOUT_CAPTURE - distance as i8);
+        assert_eq!(probe(tablebase(), &late_board), None);
+    }
+}
@@ -0,0 +1,227 @@
+use std::time::Duration;
+
+// The clock of a player gives a time budget for each move.
+// The search stops before the target time when the best move is stable, and may go beyond it when the score drops.
+
+/// Expected number of moves left to play, when the time control does not tell it
+const EXPECTED_MOVES_LEFT: u32 = 30;
+/// The maximum time of a move is at most this number of times its target time
+const MAX_TARGET_RATIO: u32 = 4;
+/// The maximum time of a move is at most this fraction of the remaining time
+const MAX_REMAINING_RATIO: u32 = 4;
+/// Time kept on the clock for the overhead of each move (thread spawning, printing, ...)
+const MOVE_OVERHEAD: Duration = Duration::from_millis(50);
+/// Number of iterations with the same best move after which the best move is stable
+const NB_STABLE_ITERATIONS: u32 = 2;
+/// The target time is multiplied by this factor when the best move is stable
+const STABLE_TIME_FACTOR: f64 = 0.5;
+/// Score loss between two iterations above which the score drops
+const SCORE_DROP_MARGIN: i64 = 100_000;
+/// The target time is multiplied by this factor when the score drops
+const SCORE_DROP_TIME_FACTOR: f64 = 2.;
+
+#[derive(Clone, Copy, Debug)]
+pub enum TimeControl {
+    /// The whole game must be played within this time
+    SuddenDeath(Duration),
+    /// `increment` is added to the clock after each move
+    Fischer {
+        initial: Duration,
+        increment: Duration,
+    },
+    /// `moves` moves must be played within `period`, then the clock is refilled with `period`
+    MovesPerPeriod { moves: u32, period: Duration },
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct TimeBudget {
+    /// Usual time spent on the move
+    pub target: Duration,
+    /// The search is cancelled after this time
+    pub maximum: Duration,
+}
+
+impl TimeBudget {
+    /// Always searches during `duration`
+    pub const fn fixed(duration: Duration) -> Self {
+        TimeBudget {
+            target: duration,
+            maximum: duration,
+        }
+    }
+
+    /// Same budget, but never more than `duration`
+    pub fn capped(self, duration: Duration) -> Self {
+        TimeBudget {
+            target: Duration::min(self.target, duration),
+            maximum: Duration::min(self.maximum, duration),
+        }
+    }
+
+    /// No new iteration of the iterative deepening is started after this time
+    pub fn soft_limit(&self, nb_stable_iterations: u32, score_drop: i64) -> Duration {
+        if self.target >= self.maximum {
+            return self.maximum;
+        }
+        let factor = if score_drop > SCORE_DROP_MARGIN {
+            SCORE_DROP_TIME_FACTOR
+        } else if nb_stable_iterations >= NB_STABLE_ITERATIONS {
+            STABLE_TIME_FACTOR
+        } else {
+            1.
+        };
+        Duration::min(self.target.mul_f64(factor), self.maximum)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Clock {
+    time_control: TimeControl,
+    remaining: Duration,
+    nb_moves: u32,
+}
+
+impl Clock {
+    pub fn new(time_control: TimeControl) -> Self {
+        let remaining = match time_control {
+            TimeControl::SuddenDeath(duration) => duration,
+            TimeControl::Fischer { initial, .. } => initial,
+            TimeControl::MovesPerPeriod { period, .. } => period,
+        };
+        Clock {
+            time_control,
+            remaining,
+            nb_moves: 0,
+        }
+    }
+
+    pub fn get_remaining(&self) -> Duration {
+        self.remaining
+    }
+
+    pub fn time_budget(&self) -> TimeBudget {
+        let available = self.remaining.saturating_sub(MOVE_OVERHEAD);
+        let target = match self.time_control {
+            TimeControl::SuddenDeath(_) => available / EXPECTED_MOVES_LEFT,
+            TimeControl::Fischer { increment, .. } => {
+                // The increment of this move is only received once it is played
+                Duration::min(available / EXPECTED_MOVES_LEFT + increment, available)
+            }
+            TimeControl::MovesPerPeriod { moves, .. } => {
+                let moves_left = moves - self.nb_moves % moves;
+                available / moves_left
+            }
+        };
+        let maximum = Duration::min(
+            target * MAX_TARGET_RATIO,
+            Duration::max(available / MAX_REMAINING_RATIO, target),
+        );
+        TimeBudget { target, maximum }
+    }
+
+    /// Called after each move of the player, with the time spent on it
+    pub fn play(&mut self, elapsed: Duration) {
+        self.remaining = self.remaining.saturating_sub(elapsed);
+        self.nb_moves += 1;
+        match self.time_control {
+            TimeControl::SuddenDeath(_) => (),
+            TimeControl::Fischer { increment, .. } => self.remaining += increment,
+            TimeControl::MovesPerPeriod { moves, period } => {
+                if self.nb_moves.is_multiple_of(moves) {
+                    self.remaining += period;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Clock, TimeBudget, TimeControl};
+    use std::time::Duration;
+
+    #[test]
+    fn fischer_budget_includes_increment() {
+        let mut clock = Clock::new(TimeControl::Fischer {
+            initial: Duration::from_millis(30_050),
+            increment: Duration::from_secs(1),
+        });
+        // 30s left after the move overhead, over 30 moves, plus the increment
+        let time_budget = clock.time_budget();
+        assert_eq!(time_budget.target, Duration::from_secs(2));
+        assert_eq!(time_budget.maximum, Duration::from_millis(7_500));
+        clock.play(Duration::from_secs(2));
+        assert_eq!(clock.get_remaining(), Duration::from_millis(29_050));
+    }
+
+    #[test]
+    fn fischer_budget_fits_in_remaining_time() {
+        let clock = Clock::new(TimeControl::Fischer {
+            initial: Duration::from_millis(550),
+            increment: Duration::from_secs(1),
+        });
+        let time_budget = clock.time_budget();
+        assert_eq!(time_budget.target, Duration::from_millis(500));
+        assert_eq!(time_budget.maximum, Duration::from_millis(500));
+    }
+
+    #[test]
+    fn moves_per_period_budget() {
+        let mut clock = Clock::new(TimeControl::MovesPerPeriod {
+            moves: 10,
+            period: Duration::from_millis(10_050),
+        });
+        let time_budget = clock.time_budget();
+        assert_eq!(time_budget.target, Duration::from_secs(1));
+        assert_eq!(time_budget.maximum, Duration::from_millis(2_500));
+        // The time saved on the first moves is spread over the moves left in the period
+        for _ in 0..5 {
+            clock.play(Duration::from_millis(500));
+        }
+        assert_eq!(clock.time_budget().target, Duration::from_millis(1_500));
+        // The clock is refilled after the last move of the period
+        for _ in 0..5 {
+            clock.play(Duration::from_millis(1_500));
+        }
+        assert_eq!(clock.get_remaining(), Duration::from_millis(10_100));
+        assert_eq!(clock.time_budget().target, Duration::from_millis(1_005));
+    }
+
+    #[test]
+    fn fixed_budget_ignores_search() {
+        let time_budget = TimeBudget::fixed(Duration::from_secs(1));
+        assert_eq!(time_budget.soft_limit(0, 0), Duration::from_secs(1));
+        assert_eq!(time_budget.soft_limit(10, 0), Duration::from_secs(1));
+        assert_eq!(time_budget.soft_limit(0, 1_000_000), Duration::from_secs(1));
+        let time_budget = time_budget.capped(Duration::from_millis(500));
+        assert_eq!(time_budget.soft_limit(0, 0), Duration::from_millis(500));
+    }
+
+    #[test]
+    fn soft_limit_depends_on_stability_and_score_drop() {
+        let time_budget = TimeBudget {
+            target: Duration::from_secs(1),
+            maximum: Duration::from_secs(4),
+        };
+        assert_eq!(time_budget.soft_limit(0, 0), Duration::from_secs(1));
+        // Stable best move
+        assert_eq!(time_budget.soft_limit(2, 0), Duration::from_millis(500));
+        // Dropping score, even with a stable best move
+        assert_eq!(time_budget.soft_limit(0, 200_000), Duration::from_secs(2));
+        assert_eq!(time_budget.soft_limit(2, 200_000), Duration::from_secs(2));
+        // Small score drop
+        assert_eq!(
+            time_budget.soft_limit(2, 50_000),
+            Duration::from_millis(500)
+        );
+        // Never beyond the maximum
+        let time_budget = TimeBudget {
+            target: Duration::from_secs(1),
+            maximum: Duration::from_millis(1_500),
+        };
+        assert_eq!(
+            time_budget.soft_limit(0, 200_000),
+            Duration::from_millis(1_500)
+        );
+    }
+}
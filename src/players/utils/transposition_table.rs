@@ -0,0 +1,163 @@
+use std::sync::atomic::{AtomicU8, AtomicU64, Ordering};
+
+// Lock-free transposition table shared between search threads (cf https://craftychess.com/hyatt/hashing.html).
+// Each entry is stored as two u64: (key ^ data, data). A torn write makes `key ^ data` mismatch, so it is ignored.
+// Keys are `Board::state_hash`: transpositions share an entry when they have the same draw counters.
+// Scores depending on boards played before the searched board are not stored (cf `alpha_beta_score`).
+
+const ENTRIES_PER_BUCKET: usize = 4;
+const WORDS_PER_ENTRY: usize = 2;
+const NO_BEST_MOVE: u64 = u8::MAX as u64;
+const OCCUPIED_FLAG: u64 = 1 << 63;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Bound {
+    Exact,
+    Lower,
+    Upper,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct TranspositionEntry {
+    pub score: i64,
+    pub depth: i8,
+    pub bound: Bound,
+    /// Index of the best move in `Board::possible_moves`
+    pub best_move: Option<usize>,
+}
+
+pub struct TranspositionTable {
+    entries: Vec<AtomicU64>,
+    nb_buckets: usize,
+    age: AtomicU8,
+}
+
+impl TranspositionTable {
+    pub fn new(size_in_mib: usize) -> Self {
+        let bucket_size = ENTRIES_PER_BUCKET * WORDS_PER_ENTRY * size_of::<AtomicU64>();
+        let max_nb_buckets = usize::max(1, (size_in_mib << 20) / bucket_size);
+        // Largest power of two, so that `key % nb_buckets` is cheap
+        let nb_buckets = 1 << max_nb_buckets.ilog2();
+        let entries = (0..nb_buckets * ENTRIES_PER_BUCKET * WORDS_PER_ENTRY)
+            .map(|_| AtomicU64::new(0))
+            .collect();
+        TranspositionTable {
+            entries,
+            nb_buckets,
+            age: AtomicU8::new(0),
+        }
+    }
+
+    /// Called before each new move search, so that entries from previous searches get replaced first
+    pub fn new_search(&self) {
+        self.age.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn get(&self, key: u64) -> Option<TranspositionEntry> {
+        for slot in self.bucket_slots(key) {
+            let (slot_key, data) = self.load(slot);
+            if data & OCCUPIED_FLAG != 0 && slot_key == key {
+                return Some(entry_of_data(data));
+            }
+        }
+        None
+    }
+
+    pub fn insert(&self, key: u64, entry: TranspositionEntry) {
+        let age = self.age.load(Ordering::Relaxed);
+        let data = data_of_entry(entry, age);
+
+        let mut replaced_slot = None;
+        let mut replaced_priority = i32::MAX;
+        for slot in self.bucket_slots(key) {
+            let (slot_key, slot_data) = self.load(slot);
+            if slot_data & OCCUPIED_FLAG == 0 {
+                if replaced_slot.is_none() {
+                    replaced_slot = Some(slot);
+                    replaced_priority = i32::MIN;
+                }
+                continue;
+            }
+            let slot_entry = entry_of_data(slot_data);
+            let slot_age = age.wrapping_sub(age_of_data(slot_data));
+            if slot_key == key {
+                // Keep a deeper result of the current search, unless the new one is exact
+                if slot_age == 0 && slot_entry.depth > entry.depth && entry.bound != Bound::Exact {
+                    return;
+                }
+                replaced_slot = Some(slot);
+                break;
+            }
+            // Old and shallow entries are replaced first
+            let priority = slot_entry.depth as i32 - 8 * slot_age as i32;
+            if priority < replaced_priority {
+                replaced_slot = Some(slot);
+                replaced_priority = priority;
+            }
+        }
+
+        let slot = replaced_slot.unwrap();
+        self.entries[slot].store(key ^ data, Ordering::Relaxed);
+        self.entries[slot + 1].store(data, Ordering::Relaxed);
+    }
+
+    fn bucket_slots(&self, key: u64) -> impl Iterator<Item = usize> {
+        let bucket = (key as usize) & (self.nb_buckets - 1);
+        let first_slot = bucket * ENTRIES_PER_BUCKET * WORDS_PER_ENTRY;
+        (0..ENTRIES_PER_BUCKET).map(move |i| first_slot + i * WORDS_PER_ENTRY)
+    }
+
+    fn load(&self, slot: usize) -> (u64, u64) {
+        let xored_key = self.entries[slot].load(Ordering::Relaxed);
+        let data = self.entries[slot + 1].load(Ordering::Relaxed);
+        (xored_key ^ data, data)
+    }
+}
+
+// Data layout: score (bits 0-31), depth (32-39), bound (40-41), best move (42-49), age (50-57), occupied (63)
+
+fn data_of_entry(entry: TranspositionEntry, age: u8) -> u64 {
+    let score = i32::try_from(entry.score).unwrap();
+    let bound = match entry.bound {
+        Bound::Exact => 0,
+        Bound::Lower => 1,
+        Bound::Upper => 2,
+    };
+    let best_move = match entry.best_move {
+        Some(index) => {
+            assert!((index as u64) < NO_BEST_MOVE);
+            index as u64
+        }
+        None => NO_BEST_MOVE,
+    };
+    (score as u32 as u64)
+        | ((entry.depth as u8 as u64) << 32)
+        | (bound << 40)
+        | (best_move << 42)
+        | ((age as u64) << 50)
+        | OCCUPIED_FLAG
+}
+
+fn entry_of_data(data: u64) -> TranspositionEntry {
+    let bound = match (data >> 40) & 0b11 {
+        0 => Bound::Exact,
+        1 => Bound::Lower,
+        2 => Bound::Upper,
+        bound => panic!("{}", bound),
+    };
+    let best_move = (data >> 42) & 0xff;
+    TranspositionEntry {
+        score: data as u32 as i32 as i64,
+        depth: (data >> 32) as u8 as i8,
+        bound,
+        best_move: if best_move == NO_BEST_MOVE {
+            None
+        } else {
+            Some(best_move as usize)
+        },
+    }
+}
+
+fn age_of_data(data: u64) -> u8 {
+    (data >> 50) as u8
+}
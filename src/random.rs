@@ -0,0 +1,57 @@
+use rand::rngs::StdRng;
+use rand::{RngExt, SeedableRng};
+use std::thread;
+use std::thread::JoinHandle;
+
+// Every random decision draws from an RNG passed explicitly (cf `Rng`): there is no global RNG.
+// The RNG of `main` is seeded by `--seed` (cf `get_seed`), and each thread spawned with `spawn_seeded` gets its own RNG, seeded from its parent's.
+// Thus, a run can be replayed with the same seed, as long as it does not depend on timings (time limits, threaded search, ...).
+
+pub type Rng = StdRng;
+
+pub fn seeded_rng(seed: u64) -> Rng {
+    Rng::seed_from_u64(seed)
+}
+
+/// Same as `thread::spawn`, but the new thread gets its own RNG, seeded from `rng`
+pub fn spawn_seeded<F, T>(rng: &mut Rng, f: F) -> JoinHandle<T>
+where
+    F: FnOnce(&mut Rng) -> T + Send + 'static,
+    T: Send + 'static,
+{
+    let seed = rng.random();
+    thread::spawn(move || f(&mut seeded_rng(seed)))
+}
+
+/// Sample of the symmetric Dirichlet distribution of parameter `alpha`, with `n` values summing to 1
+pub fn random_dirichlet(rng: &mut Rng, alpha: f64, n: usize) -> Vec<f64> {
+    let samples = (0..n).map(|_| random_gamma(rng, alpha)).collect::<Vec<_>>();
+    let sum = samples.iter().sum::<f64>();
+    samples.into_iter().map(|x| x / sum).collect()
+}
+
+/// Sample of the gamma distribution of shape `alpha` and scale 1, using the Marsaglia and Tsang method
+/// (cf <https://en.wikipedia.org/wiki/Gamma_distribution#Random_variate_generation>)
+fn random_gamma(rng: &mut Rng, alpha: f64) -> f64 {
+    if alpha < 1. {
+        // Gamma(alpha) = Gamma(alpha + 1) * U^(1 / alpha)
+        return random_gamma(rng, alpha + 1.) * rng.random::<f64>().powf(1. / alpha);
+    }
+    let d = alpha - 1. / 3.;
+    let c = 1. / (9. * d).sqrt();
+    loop {
+        let x = random_normal(rng);
+        let v = (1. + c * x).powi(3);
+        if v > 0. && rng.random::<f64>().ln() < 0.5 * x * x + d - d * v + d * v.ln() {
+            return d * v;
+        }
+    }
+}
+
+/// Sample of the standard normal distribution, using the Box-Muller transform
+fn random_normal(rng: &mut Rng) -> f64 {
+    // In (0, 1], for the logarithm
+    let u1 = 1. - rng.random::<f64>();
+    let u2 = rng.random::<f64>();
+    (-2. * u1.ln()).sqrt() * (std::f64::consts::TAU * u2).cos()
+}